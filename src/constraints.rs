@@ -0,0 +1,267 @@
+//! Constraint handling by adaptive penalties: `Penalized` adds the weighted violations of
+//! the `Constraints` of a problem to its cost. With `crate::Executor::with_constraints`,
+//! the weights follow a strategic oscillation, growing while the search stays infeasible
+//! and shrinking while it stays feasible.
+use crate::errors::LocalSearchError;
+use crate::problem::{Constraints, CostFunction, NeighborStream, Neighborhood};
+use rand::Rng;
+
+/// How the weight of each constraint evolves along the search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PenaltyAdaptation {
+    /// Weight multiplier of a constraint violated for `infeasible_iters` iterations in a
+    /// row.
+    pub increase: f64,
+    /// Weight multiplier of every constraint after `feasible_iters` feasible iterations in
+    /// a row.
+    pub decrease: f64,
+    pub infeasible_iters: u32,
+    pub feasible_iters: u32,
+    pub min_weight: f64,
+    pub max_weight: f64,
+}
+
+impl Default for PenaltyAdaptation {
+    fn default() -> Self {
+        Self {
+            increase: 2.0,
+            decrease: 0.5,
+            infeasible_iters: 10,
+            feasible_iters: 10,
+            min_weight: 1e-3,
+            max_weight: 1e9,
+        }
+    }
+}
+
+pub struct Penalized<O> {
+    problem: O,
+    weights: Vec<f64>,
+    adaptation: Option<PenaltyAdaptation>,
+    tolerance: f64,
+    infeasible_streaks: Vec<u32>,
+    feasible_streak: u32,
+}
+
+impl<O, P, N> Penalized<O>
+where
+    O: Constraints<Param = P, Neighbor = N>,
+{
+    /// Every constraint starts with weight `weight`, adapted with the default
+    /// `PenaltyAdaptation`.
+    pub fn new(problem: O, weight: f64) -> Self {
+        let num_constraints = problem.num_constraints();
+        Self {
+            problem,
+            weights: vec![weight; num_constraints],
+            adaptation: Some(PenaltyAdaptation::default()),
+            tolerance: 1e-9,
+            infeasible_streaks: vec![0; num_constraints],
+            feasible_streak: 0,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panic if there is not one weight per constraint.
+    #[must_use]
+    pub fn with_weights(mut self, weights: Vec<f64>) -> Self {
+        assert_eq!(weights.len(), self.weights.len());
+        self.weights = weights;
+        self
+    }
+
+    /// `None` keeps the weights fixed.
+    #[must_use]
+    pub fn with_adaptation(mut self, adaptation: Option<PenaltyAdaptation>) -> Self {
+        self.adaptation = adaptation;
+        self
+    }
+
+    /// Violations up to `tolerance` count as satisfied.
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn problem(&self) -> &O {
+        &self.problem
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    pub fn violations(&self, param: &P) -> Result<Vec<f64>, LocalSearchError> {
+        self.problem.violations(param)
+    }
+
+    pub fn is_feasible(&self, param: &P) -> Result<bool, LocalSearchError> {
+        Ok(self.is_satisfied(&self.violations(param)?))
+    }
+
+    fn is_satisfied(&self, violations: &[f64]) -> bool {
+        violations.iter().all(|&v| v <= self.tolerance)
+    }
+
+    fn penalty(&self, violations: &[f64]) -> f64 {
+        self.weights
+            .iter()
+            .zip(violations)
+            .map(|(w, v)| w * v)
+            .sum()
+    }
+
+    /// Update the weights given the violations of the current param. Returns whether a
+    /// weight changed.
+    pub fn adapt(&mut self, violations: &[f64]) -> bool {
+        let Some(adaptation) = self.adaptation else {
+            return false;
+        };
+        let mut changed = false;
+        if self.is_satisfied(violations) {
+            self.infeasible_streaks.fill(0);
+            self.feasible_streak += 1;
+            if self.feasible_streak >= adaptation.feasible_iters {
+                self.feasible_streak = 0;
+                for weight in &mut self.weights {
+                    *weight = (*weight * adaptation.decrease).max(adaptation.min_weight);
+                }
+                changed = true;
+            }
+            return changed;
+        }
+        self.feasible_streak = 0;
+        for ((weight, streak), &violation) in self
+            .weights
+            .iter_mut()
+            .zip(&mut self.infeasible_streaks)
+            .zip(violations)
+        {
+            if violation <= self.tolerance {
+                *streak = 0;
+                continue;
+            }
+            *streak += 1;
+            if *streak >= adaptation.infeasible_iters {
+                *streak = 0;
+                *weight = (*weight * adaptation.increase).min(adaptation.max_weight);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl<O, P, N> CostFunction for Penalized<O>
+where
+    O: CostFunction<Param = P> + Constraints<Param = P, Neighbor = N>,
+{
+    type Param = P;
+
+    fn cost(&self, param: &P) -> Result<f64, LocalSearchError> {
+        Ok(self.problem.cost(param)? + self.penalty(&self.problem.violations(param)?))
+    }
+}
+
+impl<O, P, N> Neighborhood for Penalized<O>
+where
+    O: Neighborhood<Param = P, Neighbor = N> + Constraints<Param = P, Neighbor = N>,
+{
+    type Param = P;
+    type Neighbor = N;
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &P,
+    ) -> Result<Vec<N>, LocalSearchError> {
+        self.problem.get_neighbor_moves(rng, param)
+    }
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<P, LocalSearchError> {
+        self.problem.random_param(rng)
+    }
+
+    fn neighbor_stream<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &P,
+    ) -> Result<NeighborStream<N>, LocalSearchError>
+    where
+        N: Send + 'static,
+    {
+        self.problem.neighbor_stream(rng, param)
+    }
+
+    fn get_neighbor_delta(&self, param: &P, neighbor: &N) -> Result<f64, LocalSearchError> {
+        Ok(self.problem.get_neighbor_delta(param, neighbor)?
+            + self.penalty(&self.problem.get_violation_deltas(param, neighbor)?))
+    }
+
+    fn make_move(&self, param: &P, neighbor: &N) -> Result<P, LocalSearchError> {
+        self.problem.make_move(param, neighbor)
+    }
+
+    fn apply_move(&self, param: &mut P, neighbor: &N) -> Result<(), LocalSearchError> {
+        self.problem.apply_move(param, neighbor)
+    }
+
+    fn undo_move(&self, param: &mut P, neighbor: &N) -> Result<(), LocalSearchError> {
+        self.problem.undo_move(param, neighbor)
+    }
+
+    fn tabu_move(&self, param: &P, neighbor: &N) -> N
+    where
+        N: Clone,
+    {
+        self.problem.tabu_move(param, neighbor)
+    }
+
+    fn move_anchors(&self, neighbor: &N) -> Vec<usize> {
+        self.problem.move_anchors(neighbor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Penalized, PenaltyAdaptation};
+    use crate::errors::LocalSearchError;
+    use crate::problem::Constraints;
+
+    /// A single constraint `x <= 0`.
+    struct NonPositive;
+
+    impl Constraints for NonPositive {
+        type Param = f64;
+        type Neighbor = f64;
+
+        fn num_constraints(&self) -> usize {
+            1
+        }
+
+        fn violations(&self, param: &f64) -> Result<Vec<f64>, LocalSearchError> {
+            Ok(vec![param.max(0.0)])
+        }
+    }
+
+    #[test]
+    fn test_adapt() {
+        let mut problem =
+            Penalized::new(NonPositive, 1.0).with_adaptation(Some(PenaltyAdaptation {
+                infeasible_iters: 2,
+                feasible_iters: 3,
+                ..PenaltyAdaptation::default()
+            }));
+        assert!(!problem.adapt(&[1.0]));
+        assert!(problem.adapt(&[1.0]));
+        assert_eq!(problem.weights(), &[2.0]);
+        assert!(!problem.adapt(&[0.0]));
+        assert!(!problem.adapt(&[0.0]));
+        assert!(problem.adapt(&[0.0]));
+        assert_eq!(problem.weights(), &[1.0]);
+        assert!(!problem.is_feasible(&0.5).unwrap());
+        assert!(problem.is_feasible(&-0.5).unwrap());
+    }
+}
@@ -0,0 +1,117 @@
+//! Standard benchmark functions, all with a global minimum of 0.
+use crate::errors::LocalSearchError;
+use crate::problem::CostFunction;
+use std::f64::consts::PI;
+
+/// Highly multimodal, minimum at the origin. Usual domain `[-5.12, 5.12]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rastrigin {
+    pub dim: usize,
+}
+
+/// Narrow curved valley, minimum at `(1, ..., 1)`. Usual domain `[-5, 10]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rosenbrock {
+    pub dim: usize,
+}
+
+/// Nearly flat outer region with a deep hole at the origin. Usual domain
+/// `[-32.768, 32.768]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ackley {
+    pub dim: usize,
+}
+
+/// Deceptive, the second best minimum is far from the global one at
+/// `(420.9687, ..., 420.9687)`. Usual domain `[-500, 500]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Schwefel {
+    pub dim: usize,
+}
+
+impl Rastrigin {
+    pub fn bounds(&self) -> Vec<(f64, f64)> {
+        vec![(-5.12, 5.12); self.dim]
+    }
+}
+
+impl Rosenbrock {
+    pub fn bounds(&self) -> Vec<(f64, f64)> {
+        vec![(-5.0, 10.0); self.dim]
+    }
+}
+
+impl Ackley {
+    pub fn bounds(&self) -> Vec<(f64, f64)> {
+        vec![(-32.768, 32.768); self.dim]
+    }
+}
+
+impl Schwefel {
+    pub fn bounds(&self) -> Vec<(f64, f64)> {
+        vec![(-500.0, 500.0); self.dim]
+    }
+}
+
+impl CostFunction for Rastrigin {
+    type Param = Vec<f64>;
+
+    fn cost(&self, param: &Vec<f64>) -> Result<f64, LocalSearchError> {
+        Ok(10.0 * param.len() as f64
+            + param
+                .iter()
+                .map(|x| x * x - 10.0 * (2.0 * PI * x).cos())
+                .sum::<f64>())
+    }
+}
+
+impl CostFunction for Rosenbrock {
+    type Param = Vec<f64>;
+
+    fn cost(&self, param: &Vec<f64>) -> Result<f64, LocalSearchError> {
+        Ok(param
+            .windows(2)
+            .map(|x| 100.0 * (x[1] - x[0] * x[0]).powi(2) + (1.0 - x[0]).powi(2))
+            .sum())
+    }
+}
+
+impl CostFunction for Ackley {
+    type Param = Vec<f64>;
+
+    fn cost(&self, param: &Vec<f64>) -> Result<f64, LocalSearchError> {
+        let n = param.len() as f64;
+        let squares = param.iter().map(|x| x * x).sum::<f64>() / n;
+        let cosines = param.iter().map(|x| (2.0 * PI * x).cos()).sum::<f64>() / n;
+        Ok(-20.0 * (-0.2 * squares.sqrt()).exp() - cosines.exp() + 20.0 + std::f64::consts::E)
+    }
+}
+
+impl CostFunction for Schwefel {
+    type Param = Vec<f64>;
+
+    fn cost(&self, param: &Vec<f64>) -> Result<f64, LocalSearchError> {
+        Ok(418.982_887_272_433_9 * param.len() as f64
+            - param.iter().map(|x| x * x.abs().sqrt().sin()).sum::<f64>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ackley, Rastrigin, Rosenbrock, Schwefel};
+    use crate::problem::CostFunction;
+
+    #[test]
+    fn test_optima() {
+        assert!(Rastrigin { dim: 3 }.cost(&vec![0.0; 3]).unwrap().abs() < 1e-12);
+        assert!(Rosenbrock { dim: 3 }.cost(&vec![1.0; 3]).unwrap().abs() < 1e-12);
+        assert!(Ackley { dim: 3 }.cost(&vec![0.0; 3]).unwrap().abs() < 1e-12);
+        assert!(
+            Schwefel { dim: 3 }
+                .cost(&vec![420.968_746; 3])
+                .unwrap()
+                .abs()
+                < 1e-4
+        );
+    }
+}
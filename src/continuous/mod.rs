@@ -0,0 +1,395 @@
+//! Box-constrained real-valued problems. `ContinuousProblem` turns any `CostFunction`
+//! over `Vec<f64>` into a `Neighborhood` usable by every solver.
+pub mod functions;
+
+pub use functions::{Ackley, Rastrigin, Rosenbrock, Schwefel};
+
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// What to do with a coordinate leaving its bounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BoundHandling {
+    /// Project on the nearest bound.
+    #[default]
+    Clip,
+    /// Mirror the excess back into the domain.
+    Reflect,
+    /// Re-enter from the opposite bound, for periodic domains.
+    Wrap,
+}
+
+impl BoundHandling {
+    pub fn apply(self, value: f64, (lower, upper): (f64, f64)) -> f64 {
+        let width = upper - lower;
+        if (lower..=upper).contains(&value) || width <= 0.0 {
+            return value.clamp(lower, upper);
+        }
+        match self {
+            Self::Clip => value.clamp(lower, upper),
+            Self::Reflect => {
+                // Reflecting twice is a translation by twice the width
+                let offset = (value - lower).rem_euclid(2.0 * width);
+                lower
+                    + if offset > width {
+                        2.0 * width - offset
+                    } else {
+                        offset
+                    }
+            }
+            Self::Wrap => lower + (value - lower).rem_euclid(width),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StepDistribution {
+    #[default]
+    Gaussian,
+    /// Heavy tailed, for occasional long jumps.
+    Cauchy,
+    Uniform,
+}
+
+impl StepDistribution {
+    pub fn sample<R: Rng>(self, rng: &mut R) -> f64 {
+        match self {
+            Self::Gaussian => {
+                // Box-Muller transform
+                let u: f64 = 1.0 - rng.gen::<f64>();
+                (-2.0 * u.ln()).sqrt() * (2.0 * PI * rng.gen::<f64>()).cos()
+            }
+            Self::Cauchy => (PI * (rng.gen::<f64>() - 0.5)).tan(),
+            Self::Uniform => rng.gen_range(-1.0..1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContinuousMoveKind {
+    /// Perturb every coordinate.
+    Step,
+    /// Perturb a single coordinate.
+    Coordinate,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContinuousMove {
+    Step(Vec<f64>),
+    Coordinate { index: usize, step: f64 },
+}
+
+/// Step size relative to the width of the domain, adapted with Rechenberg's 1/5th success
+/// rule: every `period` generated neighborhoods, the step grows if more than a fifth of
+/// them led the solver to apply an improving move and shrinks otherwise.
+///
+/// The problem is shared with the solvers, which only borrow it, hence the atomics.
+#[derive(Debug)]
+struct StepSize {
+    sigma: AtomicU64,
+    trials: AtomicUsize,
+    successes: AtomicUsize,
+    period: Option<usize>,
+    factor: f64,
+    min_sigma: f64,
+}
+
+impl StepSize {
+    fn get(&self) -> f64 {
+        f64::from_bits(self.sigma.load(Ordering::Relaxed))
+    }
+
+    fn record_success(&self) {
+        self.successes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Adapt the step from the previous trials, then start a new one.
+    fn adapt(&self) -> f64 {
+        let sigma = self.get();
+        let Some(period) = self.period else {
+            return sigma;
+        };
+        let trials = self.trials.fetch_add(1, Ordering::Relaxed);
+        if trials < period {
+            return sigma;
+        }
+        let successes = self.successes.swap(0, Ordering::Relaxed);
+        self.trials.store(1, Ordering::Relaxed);
+        let rate = successes as f64 / trials as f64;
+        let sigma = if rate > 0.2 {
+            sigma / self.factor
+        } else if rate < 0.2 {
+            (sigma * self.factor).max(self.min_sigma)
+        } else {
+            sigma
+        }
+        .min(1.0);
+        self.sigma.store(sigma.to_bits(), Ordering::Relaxed);
+        sigma
+    }
+}
+
+/// A `CostFunction` over a box `bounds`, explored by random steps.
+#[derive(Debug)]
+pub struct ContinuousProblem<C> {
+    cost_fn: C,
+    bounds: Vec<(f64, f64)>,
+    bound_handling: BoundHandling,
+    distribution: StepDistribution,
+    kinds: Vec<ContinuousMoveKind>,
+    num_moves: usize,
+    step: StepSize,
+}
+
+impl<C> ContinuousProblem<C>
+where
+    C: CostFunction<Param = Vec<f64>>,
+{
+    /// By default 10 Gaussian steps of every coordinate are drawn per neighborhood, with
+    /// an initial step of a tenth of the domain adapted every 10 neighborhoods.
+    ///
+    /// # Panics
+    ///
+    /// Panic if a lower bound is greater than its upper bound.
+    pub fn new(cost_fn: C, bounds: Vec<(f64, f64)>) -> Self {
+        assert!(bounds.iter().all(|(lower, upper)| lower <= upper));
+        Self {
+            cost_fn,
+            bounds,
+            bound_handling: BoundHandling::default(),
+            distribution: StepDistribution::default(),
+            kinds: vec![ContinuousMoveKind::Step],
+            num_moves: 10,
+            step: StepSize {
+                sigma: AtomicU64::new(0.1_f64.to_bits()),
+                trials: AtomicUsize::new(0),
+                successes: AtomicUsize::new(0),
+                period: Some(10),
+                factor: 0.85,
+                min_sigma: 1e-12,
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn with_bound_handling(mut self, bound_handling: BoundHandling) -> Self {
+        self.bound_handling = bound_handling;
+        self
+    }
+
+    #[must_use]
+    pub fn with_distribution(mut self, distribution: StepDistribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    #[must_use]
+    pub fn with_moves(mut self, kinds: &[ContinuousMoveKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    /// Number of moves drawn per neighborhood.
+    #[must_use]
+    pub fn with_num_moves(mut self, num_moves: usize) -> Self {
+        self.num_moves = num_moves;
+        self
+    }
+
+    /// Initial step size, as a fraction of the width of each coordinate's domain.
+    #[must_use]
+    pub fn with_step_size(self, sigma: f64) -> Self {
+        self.step.sigma.store(sigma.to_bits(), Ordering::Relaxed);
+        self
+    }
+
+    /// Adapt the step every `period` generated neighborhoods, multiplying or dividing it by
+    /// `factor` (in `(0, 1)`). `None` keeps the step size fixed.
+    #[must_use]
+    pub fn with_adaptation(mut self, period: Option<usize>, factor: f64) -> Self {
+        self.step.period = period;
+        self.step.factor = factor;
+        self
+    }
+
+    pub fn cost_fn(&self) -> &C {
+        &self.cost_fn
+    }
+
+    pub fn bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    /// Current relative step size.
+    pub fn step_size(&self) -> f64 {
+        self.step.get()
+    }
+
+    /// Draw a point uniformly within the bounds.
+    pub fn init_solution<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        self.bounds
+            .iter()
+            .map(|&(lower, upper)| {
+                if lower < upper {
+                    rng.gen_range(lower..upper)
+                } else {
+                    lower
+                }
+            })
+            .collect()
+    }
+}
+
+impl<C> CostFunction for ContinuousProblem<C>
+where
+    C: CostFunction<Param = Vec<f64>>,
+{
+    type Param = Vec<f64>;
+
+    fn cost(&self, param: &Vec<f64>) -> Result<f64, LocalSearchError> {
+        self.cost_fn.cost(param)
+    }
+}
+
+impl<C> Neighborhood for ContinuousProblem<C>
+where
+    C: CostFunction<Param = Vec<f64>>,
+{
+    type Param = Vec<f64>;
+    type Neighbor = ContinuousMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<Vec<f64>, LocalSearchError> {
+        Ok(self.init_solution(rng))
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Vec<f64>,
+    ) -> Result<Vec<ContinuousMove>, LocalSearchError> {
+        if param.is_empty() || self.kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sigma = self.step.adapt();
+        let step = |rng: &mut R, index: usize| {
+            let (lower, upper) = self.bounds[index];
+            sigma * (upper - lower) * self.distribution.sample(rng)
+        };
+        Ok((0..self.num_moves)
+            .map(|_| match self.kinds.choose(rng).unwrap() {
+                ContinuousMoveKind::Step => {
+                    ContinuousMove::Step((0..param.len()).map(|index| step(rng, index)).collect())
+                }
+                ContinuousMoveKind::Coordinate => {
+                    let index = rng.gen_range(0..param.len());
+                    ContinuousMove::Coordinate {
+                        index,
+                        step: step(rng, index),
+                    }
+                }
+            })
+            .collect())
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Vec<f64>,
+        neighbor: &ContinuousMove,
+    ) -> Result<f64, LocalSearchError> {
+        Ok(self.cost_fn.cost(&self.make_move(param, neighbor)?)? - self.cost_fn.cost(param)?)
+    }
+
+    /// Improving moves applied by the solvers are the successes of the 1/5th rule.
+    fn apply_move(
+        &self,
+        param: &mut Vec<f64>,
+        neighbor: &ContinuousMove,
+    ) -> Result<(), LocalSearchError> {
+        let new_param = self.make_move(param, neighbor)?;
+        if self.cost_fn.cost(&new_param)? < self.cost_fn.cost(param)? {
+            self.step.record_success();
+        }
+        *param = new_param;
+        Ok(())
+    }
+
+    fn make_move(
+        &self,
+        param: &Vec<f64>,
+        neighbor: &ContinuousMove,
+    ) -> Result<Vec<f64>, LocalSearchError> {
+        let mut new_param = param.clone();
+        match neighbor {
+            ContinuousMove::Step(steps) => {
+                for (index, (value, step)) in new_param.iter_mut().zip(steps).enumerate() {
+                    *value = self.bound_handling.apply(*value + step, self.bounds[index]);
+                }
+            }
+            ContinuousMove::Coordinate { index, step } => {
+                new_param[*index] = self
+                    .bound_handling
+                    .apply(new_param[*index] + step, self.bounds[*index]);
+            }
+        }
+        Ok(new_param)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoundHandling, ContinuousMove, ContinuousProblem};
+    use crate::errors::LocalSearchError;
+    use crate::problem::{CostFunction, Neighborhood};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    struct Square;
+
+    impl CostFunction for Square {
+        type Param = Vec<f64>;
+
+        fn cost(&self, param: &Vec<f64>) -> Result<f64, LocalSearchError> {
+            Ok(param.iter().map(|x| x * x).sum())
+        }
+    }
+
+    #[test]
+    fn test_bound_handling() {
+        let bounds = (-1.0, 1.0);
+        assert_eq!(BoundHandling::Clip.apply(0.5, bounds), 0.5);
+        assert_eq!(BoundHandling::Clip.apply(1.5, bounds), 1.0);
+        assert_eq!(BoundHandling::Clip.apply(-3.0, bounds), -1.0);
+        assert_eq!(BoundHandling::Reflect.apply(1.5, bounds), 0.5);
+        assert_eq!(BoundHandling::Reflect.apply(-1.5, bounds), -0.5);
+        assert_eq!(BoundHandling::Reflect.apply(3.5, bounds), -0.5);
+        assert_eq!(BoundHandling::Wrap.apply(1.5, bounds), -0.5);
+        assert_eq!(BoundHandling::Wrap.apply(-1.5, bounds), 0.5);
+    }
+
+    #[test]
+    fn test_step_adaptation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let problem =
+            ContinuousProblem::new(Square, vec![(-2.0, 2.0)]).with_adaptation(Some(2), 0.5);
+        let mut param = vec![1.0];
+        let improving = ContinuousMove::Coordinate {
+            index: 0,
+            step: -0.1,
+        };
+        for _ in 0..2 {
+            problem.get_neighbor_moves(&mut rng, &param).unwrap();
+            // Evaluated neighbors do not count
+            for _ in 0..10 {
+                problem.get_neighbor_delta(&param, &improving).unwrap();
+            }
+            assert_eq!(problem.step_size(), 0.1);
+            problem.apply_move(&mut param, &improving).unwrap();
+        }
+        problem.get_neighbor_moves(&mut rng, &param).unwrap();
+        assert_eq!(problem.step_size(), 0.2);
+    }
+}
@@ -0,0 +1,282 @@
+//! Evaluation accounting: `Counting` wraps a problem and counts its full and delta
+//! evaluations in an `EvaluationCounter`, so that solvers can be compared on the same
+//! budget of evaluations whatever they evaluate per iteration.
+use crate::errors::LocalSearchError;
+use crate::problem::{
+    AugmentedNeighborhood, Constraints, Constructive, CostFunction, DynamicProblem,
+    IncrementalEvaluation, NeighborStream, Neighborhood, Relinking,
+};
+use crate::termination::{EvaluationCounter, MaxEvaluations};
+use rand::Rng;
+
+pub struct Counting<O> {
+    problem: O,
+    counter: EvaluationCounter,
+}
+
+impl<O> Counting<O> {
+    pub fn new(problem: O) -> Self {
+        Self {
+            problem,
+            counter: EvaluationCounter::new(),
+        }
+    }
+
+    /// Weight of a delta evaluation relative to a full one, see `EvaluationCounter`. It
+    /// applies to every budget of the problem, including the ones made before.
+    pub fn set_delta_weight(&self, delta_weight: f64) {
+        self.counter.set_delta_weight(delta_weight);
+    }
+
+    pub fn problem(&self) -> &O {
+        &self.problem
+    }
+
+    pub fn into_problem(self) -> O {
+        self.problem
+    }
+
+    pub fn counter(&self) -> &EvaluationCounter {
+        &self.counter
+    }
+
+    /// Weighted number of evaluations so far.
+    pub fn evaluations(&self) -> f64 {
+        self.counter.evaluations()
+    }
+
+    /// Termination criterion for `crate::Executor::with_termination`, once `max` weighted
+    /// evaluations are reached.
+    pub fn budget(&self, max: f64) -> MaxEvaluations {
+        MaxEvaluations::new(self.counter.clone(), max)
+    }
+}
+
+impl<O: CostFunction> CostFunction for Counting<O> {
+    type Param = O::Param;
+
+    fn cost(&self, param: &O::Param) -> Result<f64, LocalSearchError> {
+        self.counter.increment();
+        self.problem.cost(param)
+    }
+}
+
+impl<O: Neighborhood> Neighborhood for Counting<O> {
+    type Param = O::Param;
+    type Neighbor = O::Neighbor;
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &O::Param,
+    ) -> Result<Vec<O::Neighbor>, LocalSearchError> {
+        self.problem.get_neighbor_moves(rng, param)
+    }
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<O::Param, LocalSearchError> {
+        self.problem.random_param(rng)
+    }
+
+    fn neighbor_stream<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &O::Param,
+    ) -> Result<NeighborStream<O::Neighbor>, LocalSearchError>
+    where
+        O::Neighbor: Send + 'static,
+    {
+        self.problem.neighbor_stream(rng, param)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &O::Param,
+        neighbor: &O::Neighbor,
+    ) -> Result<f64, LocalSearchError> {
+        self.counter.increment_delta();
+        self.problem.get_neighbor_delta(param, neighbor)
+    }
+
+    fn make_move(
+        &self,
+        param: &O::Param,
+        neighbor: &O::Neighbor,
+    ) -> Result<O::Param, LocalSearchError> {
+        self.problem.make_move(param, neighbor)
+    }
+
+    fn apply_move(
+        &self,
+        param: &mut O::Param,
+        neighbor: &O::Neighbor,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.apply_move(param, neighbor)
+    }
+
+    fn undo_move(
+        &self,
+        param: &mut O::Param,
+        neighbor: &O::Neighbor,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.undo_move(param, neighbor)
+    }
+
+    fn tabu_move(&self, param: &O::Param, neighbor: &O::Neighbor) -> O::Neighbor
+    where
+        O::Neighbor: Clone,
+    {
+        self.problem.tabu_move(param, neighbor)
+    }
+
+    fn move_anchors(&self, neighbor: &O::Neighbor) -> Vec<usize> {
+        self.problem.move_anchors(neighbor)
+    }
+}
+
+/// An augmented delta is counted as a delta evaluation.
+impl<O: AugmentedNeighborhood<F>, F> AugmentedNeighborhood<F> for Counting<O> {
+    type Param = O::Param;
+    type Neighbor = O::Neighbor;
+    type Penalty = O::Penalty;
+
+    fn get_neighbor_augmented_delta(
+        &self,
+        param: &O::Param,
+        neighbor: &O::Neighbor,
+        penalty: &O::Penalty,
+    ) -> Result<f64, LocalSearchError> {
+        self.counter.increment_delta();
+        self.problem
+            .get_neighbor_augmented_delta(param, neighbor, penalty)
+    }
+
+    fn features(&self, param: &O::Param) -> Result<Vec<(F, f64)>, LocalSearchError> {
+        self.problem.features(param)
+    }
+
+    fn update_penalty(
+        &self,
+        param: &O::Param,
+        penalty: &mut O::Penalty,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.update_penalty(param, penalty)
+    }
+
+    fn feature_anchors(&self, feature: &F) -> Vec<usize> {
+        self.problem.feature_anchors(feature)
+    }
+
+    fn number_of_features(&self, param: &O::Param) -> Result<u32, LocalSearchError> {
+        self.problem.number_of_features(param)
+    }
+}
+
+impl<O: DynamicProblem> DynamicProblem for Counting<O> {
+    type Param = O::Param;
+    type Change = O::Change;
+
+    fn update_instance(&mut self, change: &O::Change) -> Result<(), LocalSearchError> {
+        self.problem.update_instance(change)
+    }
+
+    fn repair(&self, change: &O::Change, param: &mut O::Param) -> Result<(), LocalSearchError> {
+        self.problem.repair(change, param)
+    }
+}
+
+/// A cached delta is counted as a delta evaluation, the cache being built along with a
+/// full one.
+impl<O: IncrementalEvaluation> IncrementalEvaluation for Counting<O> {
+    type Param = O::Param;
+    type Neighbor = O::Neighbor;
+    type Cache = O::Cache;
+
+    fn init_cache(&self, param: &O::Param) -> Result<O::Cache, LocalSearchError> {
+        self.problem.init_cache(param)
+    }
+
+    fn get_cached_delta(
+        &self,
+        param: &O::Param,
+        cache: &O::Cache,
+        neighbor: &O::Neighbor,
+    ) -> Result<f64, LocalSearchError> {
+        self.counter.increment_delta();
+        self.problem.get_cached_delta(param, cache, neighbor)
+    }
+
+    fn update_cache(
+        &self,
+        param: &O::Param,
+        cache: &mut O::Cache,
+        neighbor: &O::Neighbor,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.update_cache(param, cache, neighbor)
+    }
+}
+
+/// Violations are evaluated along with the cost or its delta by `crate::Penalized`, and are
+/// not counted apart.
+impl<O, P, N> Constraints for Counting<O>
+where
+    O: Constraints<Param = P, Neighbor = N> + Neighborhood<Param = P, Neighbor = N>,
+{
+    type Param = P;
+    type Neighbor = N;
+
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+
+    fn violations(&self, param: &P) -> Result<Vec<f64>, LocalSearchError> {
+        self.problem.violations(param)
+    }
+
+    fn get_violation_deltas(&self, param: &P, neighbor: &N) -> Result<Vec<f64>, LocalSearchError> {
+        self.problem.get_violation_deltas(param, neighbor)
+    }
+}
+
+impl<O: Relinking> Relinking for Counting<O> {
+    type Param = O::Param;
+    type Neighbor = O::Neighbor;
+
+    fn moves_toward(
+        &self,
+        param: &O::Param,
+        guide: &O::Param,
+    ) -> Result<Vec<O::Neighbor>, LocalSearchError> {
+        self.problem.moves_toward(param, guide)
+    }
+}
+
+/// A greedy cost is counted as a delta evaluation.
+impl<O: Constructive> Constructive for Counting<O> {
+    type Param = O::Param;
+    type Element = O::Element;
+
+    fn empty_solution(&self) -> Result<O::Param, LocalSearchError> {
+        self.problem.empty_solution()
+    }
+
+    fn candidates(&self, partial: &O::Param) -> Result<Vec<O::Element>, LocalSearchError> {
+        self.problem.candidates(partial)
+    }
+
+    fn greedy_cost(
+        &self,
+        partial: &O::Param,
+        element: &O::Element,
+    ) -> Result<f64, LocalSearchError> {
+        self.counter.increment_delta();
+        self.problem.greedy_cost(partial, element)
+    }
+
+    fn add_element(
+        &self,
+        partial: &mut O::Param,
+        element: &O::Element,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.add_element(partial, element)
+    }
+}
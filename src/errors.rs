@@ -5,6 +5,12 @@ pub enum LocalSearchError {
     NotInitialized,
     FailGenRandomState,
     FailGenCandidateState,
+    FailUndoMove,
+    FailReadInstance(String),
+    InvalidParam(String),
+    FailCrossover(String),
+    InconsistentNeighborhood(String),
+    CostDrift(String),
     Bug,
 }
 
@@ -20,6 +26,24 @@ impl fmt::Display for LocalSearchError {
             Self::FailGenCandidateState => {
                 write!(f, "Fail to generate a candidate state")
             }
+            Self::FailUndoMove => {
+                write!(f, "Fail to undo a move")
+            }
+            Self::FailReadInstance(msg) => {
+                write!(f, "Fail to read problem instance: {msg}")
+            }
+            Self::InvalidParam(msg) => {
+                write!(f, "Invalid param: {msg}")
+            }
+            Self::FailCrossover(msg) => {
+                write!(f, "Fail to recombine parents: {msg}")
+            }
+            Self::InconsistentNeighborhood(report) => {
+                write!(f, "Inconsistent neighborhood: {report}")
+            }
+            Self::CostDrift(msg) => {
+                write!(f, "Cost drift at {msg}")
+            }
             Self::Bug => {
                 write!(f, "Bug")
             }
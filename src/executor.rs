@@ -1,14 +1,36 @@
+use crate::constraints::Penalized;
 use crate::errors::LocalSearchError;
+use crate::problem::{Constraints, CostFunction, DynamicProblem, Neighborhood};
 use crate::result::LocalSearchSolution;
 use crate::solver::Solver;
-use crate::state::State;
-use crate::termination::Status;
+use crate::state::{ElitePool, IterState, State};
+use crate::termination::{Status, TerminationCriterion};
+use crate::verify::NeighborhoodChecker;
+use rand::Rng;
+use std::fmt;
+use std::sync::mpsc::Receiver;
 use std::time;
 
+/// What to do when the tracked cost drifted from a full evaluation of the param.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DriftPolicy {
+    /// Record the drift in the state, see `IterState::get_drifts`, and continue with the
+    /// re-evaluated cost.
+    #[default]
+    Record,
+    /// Fail with `LocalSearchError::CostDrift`.
+    Error,
+}
+
+/// Called on the state after initialization and after every iteration. Hooks are `Send`
+/// so that an executor can be moved to another thread before it runs.
+type Hook<O, S, I> = Box<dyn FnMut(&mut O, &mut S, I) -> Result<I, LocalSearchError> + Send>;
+
 pub struct Executor<O, S, I> {
     solver: S,
     problem: O,
     state: Option<I>,
+    hooks: Vec<Hook<O, S, I>>,
     duration: time::Duration,
 }
 
@@ -22,6 +44,7 @@ where
             solver,
             problem,
             state: Some(I::new()),
+            hooks: Vec::new(),
             duration: time::Duration::new(0, 0),
         }
     }
@@ -39,6 +62,25 @@ where
         }
     }
 
+    /// Terminate the run once `criterion` does, in addition to the solver's own
+    /// termination. Criteria are checked in the order they were added.
+    #[must_use]
+    pub fn with_termination<C>(mut self, mut criterion: C) -> Self
+    where
+        C: TerminationCriterion<I> + Send + 'static,
+    {
+        self.hooks.push(Box::new(move |_problem, _solver, state| {
+            if state.terminated() {
+                return Ok(state);
+            }
+            match criterion.check(&state) {
+                Status::Terminated(reason) => Ok(state.terminate_with(reason)),
+                Status::NotTerminated => Ok(state),
+            }
+        }));
+        self
+    }
+
     /// # Panics
     ///
     /// Panic if
@@ -52,32 +94,262 @@ where
             return Err(LocalSearchError::NotInitialized);
         };
 
-        let mut state = self.solver.init(&mut self.problem, state)?;
+        let Self {
+            solver,
+            problem,
+            hooks,
+            ..
+        } = &mut self;
+        let state = run_solver(problem, solver, state, |problem, solver, mut state| {
+            state.time(Some(total_time.elapsed()));
+            for hook in hooks.iter_mut() {
+                state = hook(problem, solver, state)?;
+            }
+            Ok(state)
+        })?;
+        self.duration = total_time.elapsed();
+
+        Ok(LocalSearchSolution::new(self.problem, self.solver, state))
+    }
+}
+
+/// Run `solver` on `problem` from `state` until termination, calling `after_iter` after
+/// initialization and after every iteration. The loop of `Executor::run`, shared with the
+/// solvers running inner solvers.
+pub(crate) fn run_solver<O, S, I, F>(
+    problem: &mut O,
+    solver: &mut S,
+    state: I,
+    mut after_iter: F,
+) -> Result<I, LocalSearchError>
+where
+    S: Solver<O, I>,
+    I: State,
+    F: FnMut(&mut O, &mut S, I) -> Result<I, LocalSearchError>,
+{
+    let mut state = solver.init(problem, state)?;
+    state.update();
+    state = after_iter(problem, solver, state)?;
+
+    loop {
+        state = if state.terminated() {
+            state
+        } else if let Status::Terminated(reason) = solver.terminate_internal(&state) {
+            state.terminate_with(reason)
+        } else {
+            state
+        };
+
+        if state.terminated() {
+            break;
+        }
+
+        state = solver.next_iter(problem, state)?;
         state.update();
+        state.increment_iter();
+        state = after_iter(problem, solver, state)?;
 
-        loop {
-            state = if state.terminated() {
-                state
-            } else if let Status::Terminated(reason) = self.solver.terminate_internal(&state) {
-                state.terminate_with(reason)
-            } else {
-                state
-            };
+        if state.terminated() {
+            break;
+        }
+    }
+    solver.finalize(problem, state)
+}
 
-            if state.terminated() {
-                break;
+impl<O, S, P, N> Executor<O, S, IterState<P>>
+where
+    O: CostFunction<Param = P> + Neighborhood<Param = P, Neighbor = N>,
+    S: Solver<O, IterState<P>>,
+    P: Clone + PartialEq,
+    N: Clone + fmt::Debug,
+{
+    /// Debug mode: every `every` iterations, sample neighbors of the current param with
+    /// `checker` and fail with `LocalSearchError::InconsistentNeighborhood` if one of them
+    /// is inconsistent.
+    #[must_use]
+    pub fn with_neighborhood_check<R: Rng + Send + 'static>(
+        mut self,
+        every: u32,
+        checker: NeighborhoodChecker,
+        mut rng: R,
+    ) -> Self {
+        self.hooks.push(Box::new(move |problem, _solver, state| {
+            if state.get_iter() % every.max(1) != 0 {
+                return Ok(state);
             }
+            if let Some(param) = state.get_param() {
+                let report = checker.check(problem, param, &mut rng)?;
+                if !report.is_consistent() {
+                    return Err(LocalSearchError::InconsistentNeighborhood(format!(
+                        "iteration {}: {report}",
+                        state.get_iter()
+                    )));
+                }
+            }
+            Ok(state)
+        }));
+        self
+    }
 
-            state = self.solver.next_iter(&mut self.problem, state)?;
+    /// Every `interval` iterations, re-evaluate the current and best params with
+    /// `CostFunction::cost` and replace the tracked costs, which solvers accumulate from
+    /// deltas. A difference larger than `tolerance` on the current cost is handled
+    /// according to `policy`.
+    #[must_use]
+    pub fn with_resync(mut self, interval: u32, tolerance: f64, policy: DriftPolicy) -> Self {
+        self.hooks
+            .push(Box::new(move |problem, _solver, mut state| {
+                if state.get_iter() % interval.max(1) != 0 {
+                    return Ok(state);
+                }
+                let Some(param) = state.param.as_ref() else {
+                    return Ok(state);
+                };
+                let cost = problem.cost(param)?;
+                let drift = state.get_cost() - cost;
+                if drift.abs() > tolerance {
+                    match policy {
+                        DriftPolicy::Record => {
+                            state.drifts += 1;
+                            state.last_drift = Some(drift);
+                        }
+                        DriftPolicy::Error => {
+                            return Err(LocalSearchError::CostDrift(format!(
+                                "iteration {}: tracked cost {} but evaluated cost {cost}",
+                                state.get_iter(),
+                                state.get_cost()
+                            )))
+                        }
+                    }
+                }
+                state.best_cost = match state.best_param.as_ref() {
+                    Some(best_param) if best_param == param => cost,
+                    Some(best_param) => problem.cost(best_param)?,
+                    None => state.best_cost,
+                };
+                state.cost = cost;
+                Ok(state)
+            }));
+        self
+    }
+}
+
+impl<O, S, P> Executor<O, S, IterState<P>>
+where
+    O: CostFunction<Param = P> + DynamicProblem<Param = P>,
+    S: Solver<O, IterState<P>>,
+    P: Clone,
+{
+    /// Dynamic optimization: after every iteration, apply the instance changes received on
+    /// `changes`, repair the current, best and elite params, re-evaluate them and let the
+    /// search continue from them. The feasibility of the repaired params is unknown, so
+    /// the best feasible param is cleared.
+    #[must_use]
+    pub fn with_changes(mut self, changes: Receiver<O::Change>) -> Self
+    where
+        O::Change: Send + 'static,
+    {
+        self.hooks.push(Box::new(move |problem, solver, mut state| {
+            let mut changed = false;
+            while let Ok(change) = changes.try_recv() {
+                problem.update_instance(&change)?;
+                for param in [
+                    &mut state.param,
+                    &mut state.best_param,
+                    &mut state.prev_best_param,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    problem.repair(&change, param)?;
+                }
+                state.elite.update(|param, cost| {
+                    problem.repair(&change, param)?;
+                    Ok(cost)
+                })?;
+                changed = true;
+            }
+            if !changed {
+                return Ok(state);
+            }
+            if let Some(param) = state.param.as_ref() {
+                state.cost = problem.cost(param)?;
+            }
+            if let Some(best_param) = state.best_param.as_ref() {
+                state.best_cost = problem.cost(best_param)?;
+            }
+            if let Some(prev_best_param) = state.prev_best_param.as_ref() {
+                state.prev_best_cost = problem.cost(prev_best_param)?;
+            }
+            state.elite.update(|param, _| problem.cost(param))?;
+            state.best_feasible_param = None;
+            state.best_feasible_cost = f64::INFINITY;
             state.update();
-            state.increment_iter();
+            solver.problem_changed();
+            Ok(state)
+        }));
+        self
+    }
+}
 
-            if state.terminated() {
-                break;
+impl<O, S, P, N> Executor<Penalized<O>, S, IterState<P>>
+where
+    O: CostFunction<Param = P> + Constraints<Param = P, Neighbor = N>,
+    S: Solver<Penalized<O>, IterState<P>>,
+    P: Clone,
+{
+    /// After every iteration, record the feasibility of the current param in the state and
+    /// adapt the penalty weights. When a weight changes, the current and best costs are
+    /// re-evaluated with the new weights.
+    #[must_use]
+    pub fn with_constraints(mut self) -> Self {
+        self.hooks.push(Box::new(|problem, _solver, mut state| {
+            let Some(param) = state.get_param() else {
+                return Ok(state);
+            };
+            let violations = problem.violations(param)?;
+            state.feasibility(problem.is_feasible(param)?);
+            if problem.adapt(&violations) {
+                if let Some(param) = state.param.as_ref() {
+                    state.cost = problem.cost(param)?;
+                }
+                if let Some(best_param) = state.best_param.as_ref() {
+                    state.best_cost = problem.cost(best_param)?;
+                }
             }
-        }
-        self.duration = total_time.elapsed();
+            Ok(state)
+        }));
+        self
+    }
+}
 
-        Ok(LocalSearchSolution::new(self.problem, self.solver, state))
+impl<O, S, P> Executor<O, S, IterState<P>>
+where
+    S: Solver<O, IterState<P>>,
+    P: Clone + Send + 'static,
+{
+    /// Keep the diverse best params visited in `pool`, stored in the state. `distance`
+    /// measures how far apart two params are.
+    #[must_use]
+    pub fn with_elite_pool<D>(mut self, pool: ElitePool<P>, distance: D) -> Self
+    where
+        D: Fn(&P, &P) -> f64 + Send + 'static,
+    {
+        let mut pool = Some(pool);
+        self.hooks
+            .push(Box::new(move |_problem, _solver, mut state| {
+                if let Some(pool) = pool.take() {
+                    state.elite = pool;
+                }
+                let cost = state.get_cost();
+                if let Some(param) = state.param.as_ref() {
+                    if state.elite.accepts(param, cost, &distance) {
+                        let param = param.clone();
+                        state.elite.insert(param, cost, &distance);
+                    }
+                }
+                Ok(state)
+            }));
+        self
     }
 }
@@ -0,0 +1,387 @@
+//! Incremental evaluation: `Cached` turns a problem implementing `IncrementalEvaluation`
+//! into one whose param carries the evaluation cache, so that every solver keeps it up to
+//! date through `Neighborhood::make_move` and `Neighborhood::apply_move`. The other traits
+//! of the problem are forwarded on the param without its cache.
+use crate::errors::LocalSearchError;
+use crate::problem::{
+    AugmentedNeighborhood, Constraints, CostFunction, DynamicProblem, IncrementalEvaluation,
+    NeighborStream, Neighborhood, Relinking,
+};
+use rand::Rng;
+
+/// A param with the evaluation cache of its problem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedParam<P, C> {
+    pub param: P,
+    pub cache: C,
+}
+
+impl<P, C> CachedParam<P, C> {
+    pub fn into_param(self) -> P {
+        self.param
+    }
+}
+
+pub struct Cached<O> {
+    problem: O,
+}
+
+impl<O, P, N, C> Cached<O>
+where
+    O: IncrementalEvaluation<Param = P, Neighbor = N, Cache = C>,
+{
+    pub fn new(problem: O) -> Self {
+        Self { problem }
+    }
+
+    pub fn problem(&self) -> &O {
+        &self.problem
+    }
+
+    /// Attach a freshly initialized cache to `param`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the cache cannot be initialized.
+    pub fn param(&self, param: P) -> Result<CachedParam<P, C>, LocalSearchError> {
+        let cache = self.problem.init_cache(&param)?;
+        Ok(CachedParam { param, cache })
+    }
+}
+
+impl<O, P, C> CostFunction for Cached<O>
+where
+    O: CostFunction<Param = P> + IncrementalEvaluation<Param = P, Cache = C>,
+{
+    type Param = CachedParam<P, C>;
+
+    fn cost(&self, param: &CachedParam<P, C>) -> Result<f64, LocalSearchError> {
+        self.problem.cost(&param.param)
+    }
+}
+
+impl<O, P, N, C> Neighborhood for Cached<O>
+where
+    O: Neighborhood<Param = P, Neighbor = N>
+        + IncrementalEvaluation<Param = P, Neighbor = N, Cache = C>,
+    P: Clone,
+    C: Clone,
+{
+    type Param = CachedParam<P, C>;
+    type Neighbor = N;
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &CachedParam<P, C>,
+    ) -> Result<Vec<N>, LocalSearchError> {
+        self.problem.get_neighbor_moves(rng, &param.param)
+    }
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<CachedParam<P, C>, LocalSearchError> {
+        self.param(self.problem.random_param(rng)?)
+    }
+
+    fn neighbor_stream<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &CachedParam<P, C>,
+    ) -> Result<NeighborStream<N>, LocalSearchError>
+    where
+        N: Send + 'static,
+    {
+        self.problem.neighbor_stream(rng, &param.param)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &CachedParam<P, C>,
+        neighbor: &N,
+    ) -> Result<f64, LocalSearchError> {
+        self.problem
+            .get_cached_delta(&param.param, &param.cache, neighbor)
+    }
+
+    fn make_move(
+        &self,
+        param: &CachedParam<P, C>,
+        neighbor: &N,
+    ) -> Result<CachedParam<P, C>, LocalSearchError> {
+        let mut cache = param.cache.clone();
+        self.problem
+            .update_cache(&param.param, &mut cache, neighbor)?;
+        Ok(CachedParam {
+            param: self.problem.make_move(&param.param, neighbor)?,
+            cache,
+        })
+    }
+
+    fn apply_move(
+        &self,
+        param: &mut CachedParam<P, C>,
+        neighbor: &N,
+    ) -> Result<(), LocalSearchError> {
+        self.problem
+            .update_cache(&param.param, &mut param.cache, neighbor)?;
+        self.problem.apply_move(&mut param.param, neighbor)
+    }
+
+    /// The cache cannot be reverted from the move alone, it is rebuilt from the restored
+    /// param.
+    fn undo_move(
+        &self,
+        param: &mut CachedParam<P, C>,
+        neighbor: &N,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.undo_move(&mut param.param, neighbor)?;
+        param.cache = self.problem.init_cache(&param.param)?;
+        Ok(())
+    }
+
+    fn tabu_move(&self, param: &CachedParam<P, C>, neighbor: &N) -> N
+    where
+        N: Clone,
+    {
+        self.problem.tabu_move(&param.param, neighbor)
+    }
+
+    fn move_anchors(&self, neighbor: &N) -> Vec<usize> {
+        self.problem.move_anchors(neighbor)
+    }
+}
+
+/// The augmented delta is the one of the problem, computed without the cache.
+impl<O, P, N, C, F> AugmentedNeighborhood<F> for Cached<O>
+where
+    O: AugmentedNeighborhood<F, Param = P, Neighbor = N>
+        + IncrementalEvaluation<Param = P, Neighbor = N, Cache = C>,
+{
+    type Param = CachedParam<P, C>;
+    type Neighbor = N;
+    type Penalty = O::Penalty;
+
+    fn get_neighbor_augmented_delta(
+        &self,
+        param: &CachedParam<P, C>,
+        neighbor: &N,
+        penalty: &O::Penalty,
+    ) -> Result<f64, LocalSearchError> {
+        self.problem
+            .get_neighbor_augmented_delta(&param.param, neighbor, penalty)
+    }
+
+    fn features(&self, param: &CachedParam<P, C>) -> Result<Vec<(F, f64)>, LocalSearchError> {
+        self.problem.features(&param.param)
+    }
+
+    fn update_penalty(
+        &self,
+        param: &CachedParam<P, C>,
+        penalty: &mut O::Penalty,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.update_penalty(&param.param, penalty)
+    }
+
+    fn feature_anchors(&self, feature: &F) -> Vec<usize> {
+        self.problem.feature_anchors(feature)
+    }
+
+    fn number_of_features(&self, param: &CachedParam<P, C>) -> Result<u32, LocalSearchError> {
+        self.problem.number_of_features(&param.param)
+    }
+}
+
+/// The cache is rebuilt from the repaired param.
+impl<O, P, C> DynamicProblem for Cached<O>
+where
+    O: DynamicProblem<Param = P> + IncrementalEvaluation<Param = P, Cache = C>,
+{
+    type Param = CachedParam<P, C>;
+    type Change = O::Change;
+
+    fn update_instance(&mut self, change: &O::Change) -> Result<(), LocalSearchError> {
+        self.problem.update_instance(change)
+    }
+
+    fn repair(
+        &self,
+        change: &O::Change,
+        param: &mut CachedParam<P, C>,
+    ) -> Result<(), LocalSearchError> {
+        self.problem.repair(change, &mut param.param)?;
+        param.cache = self.problem.init_cache(&param.param)?;
+        Ok(())
+    }
+}
+
+impl<O, P, N, C> Constraints for Cached<O>
+where
+    O: Constraints<Param = P, Neighbor = N>
+        + Neighborhood<Param = P, Neighbor = N>
+        + IncrementalEvaluation<Param = P, Neighbor = N, Cache = C>,
+    P: Clone,
+    C: Clone,
+{
+    type Param = CachedParam<P, C>;
+    type Neighbor = N;
+
+    fn num_constraints(&self) -> usize {
+        self.problem.num_constraints()
+    }
+
+    fn violations(&self, param: &CachedParam<P, C>) -> Result<Vec<f64>, LocalSearchError> {
+        self.problem.violations(&param.param)
+    }
+
+    fn get_violation_deltas(
+        &self,
+        param: &CachedParam<P, C>,
+        neighbor: &N,
+    ) -> Result<Vec<f64>, LocalSearchError> {
+        self.problem.get_violation_deltas(&param.param, neighbor)
+    }
+}
+
+impl<O, P, N, C> Relinking for Cached<O>
+where
+    O: Relinking<Param = P, Neighbor = N> + IncrementalEvaluation<Param = P, Cache = C>,
+{
+    type Param = CachedParam<P, C>;
+    type Neighbor = N;
+
+    fn moves_toward(
+        &self,
+        param: &CachedParam<P, C>,
+        guide: &CachedParam<P, C>,
+    ) -> Result<Vec<N>, LocalSearchError> {
+        self.problem.moves_toward(&param.param, &guide.param)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cached;
+    use crate::errors::LocalSearchError;
+    use crate::problem::{CostFunction, DynamicProblem, IncrementalEvaluation, Neighborhood};
+    use crate::verify::NeighborhoodChecker;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Square of the sum, with the sum cached.
+    struct SquaredSum;
+
+    impl CostFunction for SquaredSum {
+        type Param = Vec<i64>;
+
+        fn cost(&self, param: &Vec<i64>) -> Result<f64, LocalSearchError> {
+            Ok(param.iter().sum::<i64>().pow(2) as f64)
+        }
+    }
+
+    impl Neighborhood for SquaredSum {
+        type Param = Vec<i64>;
+        type Neighbor = (usize, i64);
+
+        fn get_neighbor_moves<R: Rng>(
+            &self,
+            rng: &mut R,
+            param: &Vec<i64>,
+        ) -> Result<Vec<(usize, i64)>, LocalSearchError> {
+            Ok((0..10)
+                .map(|_| (rng.gen_range(0..param.len()), rng.gen_range(-5..=5)))
+                .collect())
+        }
+
+        fn get_neighbor_delta(
+            &self,
+            param: &Vec<i64>,
+            neighbor: &(usize, i64),
+        ) -> Result<f64, LocalSearchError> {
+            Ok(self.cost(&self.make_move(param, neighbor)?)? - self.cost(param)?)
+        }
+
+        fn make_move(
+            &self,
+            param: &Vec<i64>,
+            neighbor: &(usize, i64),
+        ) -> Result<Vec<i64>, LocalSearchError> {
+            let mut new_param = param.clone();
+            new_param[neighbor.0] = neighbor.1;
+            Ok(new_param)
+        }
+    }
+
+    impl IncrementalEvaluation for SquaredSum {
+        type Param = Vec<i64>;
+        type Neighbor = (usize, i64);
+        type Cache = i64;
+
+        fn init_cache(&self, param: &Vec<i64>) -> Result<i64, LocalSearchError> {
+            Ok(param.iter().sum())
+        }
+
+        fn get_cached_delta(
+            &self,
+            param: &Vec<i64>,
+            sum: &i64,
+            &(i, value): &(usize, i64),
+        ) -> Result<f64, LocalSearchError> {
+            Ok(((sum - param[i] + value).pow(2) - sum.pow(2)) as f64)
+        }
+
+        fn update_cache(
+            &self,
+            param: &Vec<i64>,
+            sum: &mut i64,
+            &(i, value): &(usize, i64),
+        ) -> Result<(), LocalSearchError> {
+            *sum += value - param[i];
+            Ok(())
+        }
+    }
+
+    /// A change forces a value at an index.
+    impl DynamicProblem for SquaredSum {
+        type Param = Vec<i64>;
+        type Change = (usize, i64);
+
+        fn update_instance(&mut self, _change: &(usize, i64)) -> Result<(), LocalSearchError> {
+            Ok(())
+        }
+
+        fn repair(
+            &self,
+            &(i, value): &(usize, i64),
+            param: &mut Vec<i64>,
+        ) -> Result<(), LocalSearchError> {
+            param[i] = value;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cached() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let problem = Cached::new(SquaredSum);
+        let mut param = problem.param(vec![3, -1, 4, 1, -5]).unwrap();
+        for _ in 0..20 {
+            let report = NeighborhoodChecker::new(10, 1e-9)
+                .check(&problem, &param, &mut rng)
+                .unwrap();
+            assert!(report.is_consistent(), "{report}");
+            let neighbor = problem.get_neighbor_moves(&mut rng, &param).unwrap()[0];
+            problem.apply_move(&mut param, &neighbor).unwrap();
+            assert_eq!(param, problem.param(param.param.clone()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_cached_repair() {
+        let mut problem = Cached::new(SquaredSum);
+        let mut param = problem.param(vec![3, -1, 4]).unwrap();
+        problem.update_instance(&(1, 5)).unwrap();
+        problem.repair(&(1, 5), &mut param).unwrap();
+        assert_eq!(param.param, vec![3, 5, 4]);
+        assert_eq!(param.cache, 12);
+    }
+}
@@ -1,15 +1,34 @@
+pub mod constraints;
+pub mod continuous;
+pub mod counting;
 pub mod errors;
 pub mod executor;
+pub mod incremental;
 pub mod problem;
+pub mod problems;
+pub mod relinking;
+pub mod representation;
 pub mod result;
 pub mod solver;
 pub mod state;
 pub mod termination;
+pub mod tuning;
+pub mod verify;
 
+pub use constraints::{Penalized, PenaltyAdaptation};
+pub use counting::Counting;
 pub use errors::LocalSearchError;
-pub use executor::Executor;
-pub use problem::{AugmentedNeighborhood, CostFunction, Neighborhood, Penalty};
+pub use executor::{DriftPolicy, Executor};
+pub use incremental::{Cached, CachedParam};
+pub use problem::{
+    AugmentedNeighborhood, Constraints, Constructive, CostFunction, DynamicProblem,
+    IncrementalEvaluation, NeighborStream, Neighborhood, Penalty, PenaltyPolicy, Relinking,
+};
 pub use result::LocalSearchSolution;
-pub use solver::{GuidedLocalSearch, Solver, TabuSearch, VariableNeighborhood};
-pub use state::{IterState, State};
-pub use termination::{Reason, Status};
+pub use solver::{
+    Grasp, GuidedLocalSearch, Memetic, NeighborSelection, Pipeline, ReactiveTabuSearch, Solver,
+    StallPolicy, TabuSearch, VariableNeighborhood,
+};
+pub use state::{ElitePool, IterState, State};
+pub use termination::{EvaluationCounter, Reason, Status, TerminationCriterion};
+pub use verify::NeighborhoodChecker;
@@ -43,6 +43,92 @@ impl<F: Hash + Eq> Penalty<F> {
     pub fn utility(&self, feature: &F, cost: f64) -> f64 {
         cost / (1.0 + self.get(feature))
     }
+
+    /// Indices of the features of maximum utility among `features` with their costs.
+    pub fn max_utility(&self, features: &[(F, f64)]) -> Vec<usize> {
+        let utilities: Vec<f64> = features
+            .iter()
+            .map(|(feature, cost)| self.utility(feature, *cost))
+            .collect();
+        let max = utilities.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (0..utilities.len())
+            .filter(|&i| utilities[i] >= max - f64::EPSILON * max.abs().max(1.0))
+            .collect()
+    }
+
+    /// Multiply every penalty by `factor`, forgetting the ones which vanish.
+    pub fn decay(&mut self, factor: f64) {
+        for val in self.values.values_mut() {
+            *val *= factor;
+        }
+        self.values.retain(|_, val| *val > f64::EPSILON);
+    }
+
+    /// Rescale the penalties so that the largest one is `max`.
+    pub fn normalize(&mut self, max: f64) {
+        let largest = self.values.values().copied().fold(0.0, f64::max);
+        if largest > 0.0 {
+            for val in self.values.values_mut() {
+                *val *= max / largest;
+            }
+        }
+    }
+
+    pub fn cap(&mut self, max: f64) {
+        for val in self.values.values_mut() {
+            *val = val.min(max);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.values.clear();
+    }
+
+    /// The `k` most penalized features with their penalties, in decreasing order.
+    pub fn top_features(&self, k: usize) -> Vec<(&F, f64)> {
+        let mut features: Vec<(&F, f64)> = self.values.iter().map(|(f, val)| (f, *val)).collect();
+        features.sort_by(|a, b| b.1.total_cmp(&a.1));
+        features.truncate(k);
+        features
+    }
+}
+
+/// How `crate::GuidedLocalSearch` keeps its penalties from swamping the objective.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PenaltyPolicy {
+    /// Multiply every penalty by `decay` every `decay_interval` iterations, never when 0.
+    pub decay: f64,
+    pub decay_interval: u32,
+    /// Rescale the penalties after each penalization so that the largest one is this.
+    pub normalize: Option<f64>,
+    /// Clear the penalties when a new best param is found.
+    pub reset_on_best: bool,
+    /// Maximum penalty of a feature.
+    pub cap: f64,
+    /// Record a `PenaltySnapshot` every `snapshot_interval` iterations, never when 0.
+    pub snapshot_interval: u32,
+}
+
+/// Penalties only grow by default.
+impl Default for PenaltyPolicy {
+    fn default() -> Self {
+        Self {
+            decay: 1.0,
+            decay_interval: 0,
+            normalize: None,
+            reset_on_best: false,
+            cap: f64::INFINITY,
+            snapshot_interval: 0,
+        }
+    }
+}
+
+/// The penalties of `crate::GuidedLocalSearch` at an iteration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PenaltySnapshot<F: Hash + Eq> {
+    pub iter: u32,
+    pub lambda: f64,
+    pub values: HashMap<F, f64>,
 }
 
 /// Given a set of parameter, the problem needs to know the cost of that parameters
@@ -52,6 +138,11 @@ pub trait CostFunction {
     fn cost(&self, param: &Self::Param) -> Result<f64, LocalSearchError>;
 }
 
+/// Neighbors generated on demand, see `Neighborhood::neighbor_stream`. A stream cannot be
+/// cloned: solvers holding a pending stream drop it when cloned, and the clone generates
+/// new neighbors.
+pub type NeighborStream<N> = Box<dyn Iterator<Item = N> + Send>;
+
 /// A problem needs to know how to get to other neighbors of a parameter.
 pub trait Neighborhood {
     type Param;
@@ -63,6 +154,26 @@ pub trait Neighborhood {
         param: &Self::Param,
     ) -> Result<Vec<Self::Neighbor>, LocalSearchError>;
 
+    /// A new param, random or constructed, used by `crate::solver::StallPolicy` to restart.
+    /// Fails with `LocalSearchError::FailGenRandomState` by default.
+    fn random_param<R: Rng>(&self, _rng: &mut R) -> Result<Self::Param, LocalSearchError> {
+        Err(LocalSearchError::FailGenRandomState)
+    }
+
+    /// Neighbors of `param`, generated lazily as the solvers consume them. Solvers only
+    /// pull the neighbors they evaluate, so overriding it avoids materializing large
+    /// neighborhoods. Defaults to iterating over `get_neighbor_moves`.
+    fn neighbor_stream<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Self::Param,
+    ) -> Result<NeighborStream<Self::Neighbor>, LocalSearchError>
+    where
+        Self::Neighbor: Send + 'static,
+    {
+        Ok(Box::new(self.get_neighbor_moves(rng, param)?.into_iter()))
+    }
+
     /// To avoid costly calculation, we only consider the delta of moving from a param
     /// to its neighbor.
     fn get_neighbor_delta(
@@ -76,6 +187,46 @@ pub trait Neighborhood {
         param: &Self::Param,
         neighbor: &Self::Neighbor,
     ) -> Result<Self::Param, LocalSearchError>;
+
+    /// Apply `neighbor` to `param` in place. Defaults to replacing `param` by
+    /// `make_move`; override it when cloning the param is costly, the solvers only apply
+    /// accepted moves through it.
+    fn apply_move(
+        &self,
+        param: &mut Self::Param,
+        neighbor: &Self::Neighbor,
+    ) -> Result<(), LocalSearchError> {
+        *param = self.make_move(param, neighbor)?;
+        Ok(())
+    }
+
+    /// Revert `apply_move(param, neighbor)`. Fails with `LocalSearchError::FailUndoMove`
+    /// by default.
+    fn undo_move(
+        &self,
+        _param: &mut Self::Param,
+        _neighbor: &Self::Neighbor,
+    ) -> Result<(), LocalSearchError> {
+        Err(LocalSearchError::FailUndoMove)
+    }
+
+    /// The move stored in the tabu list of `crate::TabuSearch` once `neighbor` has been
+    /// applied to `param`. Defaults to the applied move itself; override it to forbid the
+    /// reverse move instead, e.g. moving a vertex back to its previous color.
+    fn tabu_move(&self, _param: &Self::Param, neighbor: &Self::Neighbor) -> Self::Neighbor
+    where
+        Self::Neighbor: Clone,
+    {
+        neighbor.clone()
+    }
+
+    /// Elements of `param` a neighbor modifies, e.g. the row of a moved queen. The first
+    /// anchor is the element the neighbor is looked up from. Only used by
+    /// `crate::solver::NeighborSelection::CandidateList`, which degrades to best
+    /// improvement without anchors.
+    fn move_anchors(&self, _neighbor: &Self::Neighbor) -> Vec<usize> {
+        Vec::new()
+    }
 }
 
 /// To use `crate::GuidedLocalSearch`, we need to also implement `AugmentedNeighborhood` /// trait on top of `Neighborhood` trait.
@@ -91,16 +242,154 @@ pub trait AugmentedNeighborhood<F> {
         penalty: &Self::Penalty,
     ) -> Result<f64, LocalSearchError>;
 
+    /// Features present in `param` with their costs, e.g. the edges of a tour with their
+    /// lengths. At a local minimum, the solver penalizes the features of maximum
+    /// `Penalty::utility`. Defaults to none, the solver then calls `update_penalty`.
+    fn features(&self, _param: &Self::Param) -> Result<Vec<(F, f64)>, LocalSearchError> {
+        Ok(Vec::new())
+    }
+
+    /// Custom penalization of the features of `param` at a local minimum, only called when
+    /// `features` returns none. Does nothing by default.
     fn update_penalty(
         &self,
-        param: &Self::Param,
-        penalty: &mut Self::Penalty,
-    ) -> Result<(), LocalSearchError>;
+        _param: &Self::Param,
+        _penalty: &mut Self::Penalty,
+    ) -> Result<(), LocalSearchError> {
+        Ok(())
+    }
+
+    /// Anchors of the moves affected by `feature`, see `Neighborhood::move_anchors`. With
+    /// `crate::GuidedLocalSearch::with_fast_local_search`, penalizing `feature` clears
+    /// their "don't look" bits.
+    fn feature_anchors(&self, _feature: &F) -> Vec<usize> {
+        Vec::new()
+    }
 
     /// Compute number of features
     fn number_of_features(&self, param: &Self::Param) -> Result<u32, LocalSearchError>;
 }
 
+/// To evaluate deltas with auxiliary data derived from the param, e.g. a delta matrix or
+/// conflict counts. Wrap the problem in `crate::incremental::Cached` to have the cache
+/// carried along with the param and updated by the solvers.
+pub trait IncrementalEvaluation {
+    type Param;
+    type Neighbor;
+    type Cache;
+
+    fn init_cache(&self, param: &Self::Param) -> Result<Self::Cache, LocalSearchError>;
+
+    fn get_cached_delta(
+        &self,
+        param: &Self::Param,
+        cache: &Self::Cache,
+        neighbor: &Self::Neighbor,
+    ) -> Result<f64, LocalSearchError>;
+
+    /// Update `cache` for `neighbor` being applied. `param` is the param before the move.
+    fn update_cache(
+        &self,
+        param: &Self::Param,
+        cache: &mut Self::Cache,
+        neighbor: &Self::Neighbor,
+    ) -> Result<(), LocalSearchError>;
+}
+
+/// Constraints declared apart from the objective. Wrap the problem in
+/// `crate::constraints::Penalized` to search with weighted penalties on the violations.
+pub trait Constraints {
+    type Param;
+    type Neighbor;
+
+    fn num_constraints(&self) -> usize;
+
+    /// Violation amount of each constraint, 0 when it is satisfied.
+    fn violations(&self, param: &Self::Param) -> Result<Vec<f64>, LocalSearchError>;
+
+    /// Change of the violation amounts when moving from `param` to `neighbor`. Defaults to
+    /// evaluating the violations of the neighbor.
+    fn get_violation_deltas(
+        &self,
+        param: &<Self as Constraints>::Param,
+        neighbor: &<Self as Constraints>::Neighbor,
+    ) -> Result<Vec<f64>, LocalSearchError>
+    where
+        Self: Neighborhood<
+            Param = <Self as Constraints>::Param,
+            Neighbor = <Self as Constraints>::Neighbor,
+        >,
+    {
+        let new_param = self.make_move(param, neighbor)?;
+        Ok(self
+            .violations(&new_param)?
+            .into_iter()
+            .zip(self.violations(param)?)
+            .map(|(new, old)| new - old)
+            .collect())
+    }
+}
+
+/// To relink params with `crate::relinking`, the problem needs to know which of its moves
+/// lead toward a guiding param.
+pub trait Relinking {
+    type Param;
+    type Neighbor;
+
+    /// Moves of `param` bringing it closer to `guide`, empty once `param` equals `guide`.
+    fn moves_toward(
+        &self,
+        param: &Self::Param,
+        guide: &Self::Param,
+    ) -> Result<Vec<Self::Neighbor>, LocalSearchError>;
+}
+
+/// To build solutions from scratch with `crate::Grasp`, the problem adds elements one at a
+/// time to a partial solution, e.g. the next city of a tour.
+pub trait Constructive {
+    type Param;
+    type Element;
+
+    fn empty_solution(&self) -> Result<Self::Param, LocalSearchError>;
+
+    /// Elements that can be added to `partial`, empty once the solution is complete.
+    fn candidates(&self, partial: &Self::Param) -> Result<Vec<Self::Element>, LocalSearchError>;
+
+    /// Increase of the cost when adding `element` to `partial`, the greedy criterion.
+    fn greedy_cost(
+        &self,
+        partial: &Self::Param,
+        element: &Self::Element,
+    ) -> Result<f64, LocalSearchError>;
+
+    fn add_element(
+        &self,
+        partial: &mut Self::Param,
+        element: &Self::Element,
+    ) -> Result<(), LocalSearchError>;
+}
+
+/// Problems whose instance can change while they are being solved, see
+/// `crate::Executor::with_changes`.
+pub trait DynamicProblem {
+    type Param;
+    type Change;
+
+    /// Apply `change` to the instance, e.g. add a customer or change a distance.
+    fn update_instance(&mut self, change: &Self::Change) -> Result<(), LocalSearchError>;
+
+    /// Make `param` a valid solution of the updated instance, e.g. insert the new
+    /// customer somewhere. Called on the current and the best params after
+    /// `update_instance`. Does nothing by default.
+    fn repair(
+        &self,
+        _change: &Self::Change,
+        _param: &mut Self::Param,
+    ) -> Result<(), LocalSearchError> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Penalty;
@@ -146,4 +435,36 @@ mod tests {
         penalty.values.insert(1, 2.0);
         assert_eq!(penalty.utility(&1, 10.0), 10.0 / 3.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_penalty_management() {
+        let mut penalty = Penalty::<i32>::new(0.5);
+        penalty.values.insert(1, 4.0);
+        penalty.values.insert(2, 1.0);
+        penalty.values.insert(3, 2.0);
+        penalty.cap(3.0);
+        assert_eq!(penalty.get(&1), 3.0);
+        penalty.normalize(6.0);
+        assert_eq!(penalty.get(&2), 2.0);
+        assert_eq!(penalty.top_features(2), vec![(&1, 6.0), (&3, 4.0)]);
+        penalty.decay(0.5);
+        assert_eq!(penalty.get(&1), 3.0);
+        penalty.decay(0.0);
+        assert!(penalty.values.is_empty());
+        penalty.update(1);
+        penalty.reset();
+        assert_eq!(penalty.get(&1), 0.0);
+    }
+
+    #[test]
+    fn test_penalty_max_utility() {
+        let mut penalty = Penalty::<i32>::new(0.5);
+        penalty.values.insert(1, 1.0);
+        // Utilities 3, 6 / 2 = 3 and 2
+        assert_eq!(
+            penalty.max_utility(&[(0, 3.0), (1, 6.0), (2, 2.0)]),
+            vec![0, 1]
+        );
+        assert!(penalty.max_utility(&[]).is_empty());
+    }
+}
@@ -0,0 +1,413 @@
+//! Graph k-coloring: assign one of `k` colors to every vertex so that the number of
+//! edges whose endpoints share a color (conflicts) is minimized.
+use super::{parse_token, read_instance};
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood};
+use crate::solver::TabuSearch;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::path::Path;
+
+/// Undirected simple graph stored as adjacency lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Graph {
+    adjacency: Vec<Vec<usize>>,
+    num_edges: usize,
+}
+
+impl Graph {
+    pub fn new(num_vertices: usize) -> Self {
+        Self {
+            adjacency: vec![Vec::new(); num_vertices],
+            num_edges: 0,
+        }
+    }
+
+    /// Add the edge `{u, v}`. Self-loops and duplicated edges are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panic if `u` or `v` is not a vertex of the graph.
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        if u == v || self.adjacency[u].contains(&v) {
+            return;
+        }
+        self.adjacency[u].push(v);
+        self.adjacency[v].push(u);
+        self.num_edges += 1;
+    }
+
+    /// Parse a graph in the DIMACS `.col` format. Vertices are renumbered from 0.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the problem line is missing or an edge is malformed.
+    pub fn from_dimacs(input: &str) -> Result<Self, LocalSearchError> {
+        let mut graph: Option<Self> = None;
+        for line in input.lines() {
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("p") => {
+                    // `p edge <vertices> <edges>`, some files use `col` instead of `edge`
+                    tokens.next();
+                    graph = Some(Self::new(parse_token(tokens.next(), "number of vertices")?));
+                }
+                Some("e") => {
+                    let Some(graph) = graph.as_mut() else {
+                        return Err(LocalSearchError::FailReadInstance(
+                            "edge declared before the problem line".to_owned(),
+                        ));
+                    };
+                    let u: usize = parse_token(tokens.next(), "edge endpoint")?;
+                    let v: usize = parse_token(tokens.next(), "edge endpoint")?;
+                    let n = graph.num_vertices();
+                    if u == 0 || v == 0 || u > n || v > n {
+                        return Err(LocalSearchError::FailReadInstance(format!(
+                            "edge ({u}, {v}) out of range"
+                        )));
+                    }
+                    graph.add_edge(u - 1, v - 1);
+                }
+                _ => {}
+            }
+        }
+        graph.ok_or_else(|| LocalSearchError::FailReadInstance("missing problem line".to_owned()))
+    }
+
+    /// Read a DIMACS `.col` file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed.
+    pub fn read_dimacs<P: AsRef<Path>>(path: P) -> Result<Self, LocalSearchError> {
+        Self::from_dimacs(&read_instance(path)?)
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.num_edges
+    }
+
+    pub fn neighbors(&self, vertex: usize) -> &[usize] {
+        &self.adjacency[vertex]
+    }
+}
+
+/// A color assignment together with its conflict table, so that the delta of any
+/// recoloring can be read in O(1).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coloring {
+    colors: Vec<usize>,
+    /// `table[v * k + c]` is the number of neighbors of `v` colored with `c`.
+    table: Vec<u32>,
+    num_colors: usize,
+    conflicts: usize,
+}
+
+impl Coloring {
+    pub fn colors(&self) -> &[usize] {
+        &self.colors
+    }
+
+    pub fn num_conflicts(&self) -> usize {
+        self.conflicts
+    }
+
+    /// Number of neighbors of `vertex` colored with `color`.
+    pub fn adjacent_colors(&self, vertex: usize, color: usize) -> u32 {
+        self.table[vertex * self.num_colors + color]
+    }
+
+    pub fn is_conflicting(&self, vertex: usize) -> bool {
+        self.adjacent_colors(vertex, self.colors[vertex]) > 0
+    }
+
+    fn recolor(&mut self, graph: &Graph, vertex: usize, color: usize) {
+        let k = self.num_colors;
+        let old = self.colors[vertex];
+        if old == color {
+            return;
+        }
+        self.conflicts = self.conflicts + self.table[vertex * k + color] as usize
+            - self.table[vertex * k + old] as usize;
+        for &u in graph.neighbors(vertex) {
+            self.table[u * k + old] -= 1;
+            self.table[u * k + color] += 1;
+        }
+        self.colors[vertex] = color;
+    }
+}
+
+/// Move `vertex` to `color`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Recolor {
+    pub vertex: usize,
+    pub color: usize,
+}
+
+/// The k-coloring problem on a graph, minimizing the number of conflicting edges.
+///
+/// The neighborhood only recolors vertices that are currently in conflict.
+#[derive(Clone, Debug)]
+pub struct GraphColoring {
+    graph: Graph,
+    num_colors: usize,
+}
+
+impl GraphColoring {
+    pub fn new(graph: Graph, num_colors: usize) -> Self {
+        Self { graph, num_colors }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn num_colors(&self) -> usize {
+        self.num_colors
+    }
+
+    /// Build the conflict table of a color assignment.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the assignment does not color every vertex with one of the
+    /// `k` colors.
+    pub fn coloring(&self, colors: Vec<usize>) -> Result<Coloring, LocalSearchError> {
+        let k = self.num_colors;
+        if colors.len() != self.graph.num_vertices() {
+            return Err(LocalSearchError::InvalidParam(format!(
+                "{} colors for {} vertices",
+                colors.len(),
+                self.graph.num_vertices()
+            )));
+        }
+        if let Some(&color) = colors.iter().find(|&&c| c >= k) {
+            return Err(LocalSearchError::InvalidParam(format!(
+                "color {color} out of range 0..{k}"
+            )));
+        }
+        let mut table = vec![0; colors.len() * k];
+        let mut conflicts = 0;
+        for (v, &color) in colors.iter().enumerate() {
+            for &u in self.graph.neighbors(v) {
+                table[u * k + color] += 1;
+                if u < v && colors[u] == color {
+                    conflicts += 1;
+                }
+            }
+        }
+        Ok(Coloring {
+            colors,
+            table,
+            num_colors: k,
+            conflicts,
+        })
+    }
+
+    /// Draw a uniformly random color assignment.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the problem has no color.
+    pub fn init_solution<R: Rng>(&self, rng: &mut R) -> Result<Coloring, LocalSearchError> {
+        if self.num_colors == 0 {
+            return Err(LocalSearchError::FailGenRandomState);
+        }
+        let colors = (0..self.graph.num_vertices())
+            .map(|_| rng.gen_range(0..self.num_colors))
+            .collect();
+        self.coloring(colors)
+    }
+
+    /// The Tabucol configuration of `TabuSearch`: the whole conflicting neighborhood is
+    /// evaluated at each iteration, the best allowed recolor is always applied, moving a
+    /// vertex back to its previous color is tabu for `tenure` iterations, and tabu moves
+    /// improving the best cost are allowed.
+    pub fn tabucol<R: Rng>(tenure: usize, rng: R) -> TabuSearch<R, Recolor> {
+        TabuSearch::new(None, tenure, rng)
+            .with_aspiration(true)
+            .with_always_accept(true)
+    }
+}
+
+impl CostFunction for GraphColoring {
+    type Param = Coloring;
+
+    fn cost(&self, param: &Coloring) -> Result<f64, LocalSearchError> {
+        let conflicts = (0..self.graph.num_vertices())
+            .map(|v| {
+                self.graph
+                    .neighbors(v)
+                    .iter()
+                    .filter(|&&u| u < v && param.colors[u] == param.colors[v])
+                    .count()
+            })
+            .sum::<usize>();
+        Ok(conflicts as f64)
+    }
+}
+
+impl Neighborhood for GraphColoring {
+    type Param = Coloring;
+    type Neighbor = Recolor;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<Coloring, LocalSearchError> {
+        self.init_solution(rng)
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Coloring,
+    ) -> Result<Vec<Recolor>, LocalSearchError> {
+        let mut moves: Vec<Recolor> = (0..self.graph.num_vertices())
+            .filter(|&v| param.is_conflicting(v))
+            .flat_map(|vertex| {
+                (0..self.num_colors)
+                    .filter(move |&color| color != param.colors[vertex])
+                    .map(move |color| Recolor { vertex, color })
+            })
+            .collect();
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Coloring,
+        neighbor: &Recolor,
+    ) -> Result<f64, LocalSearchError> {
+        let Recolor { vertex, color } = *neighbor;
+        let old = param.colors[vertex];
+        Ok(f64::from(param.adjacent_colors(vertex, color))
+            - f64::from(param.adjacent_colors(vertex, old)))
+    }
+
+    fn make_move(
+        &self,
+        param: &Coloring,
+        neighbor: &Recolor,
+    ) -> Result<Coloring, LocalSearchError> {
+        let mut new_param = param.clone();
+        new_param.recolor(&self.graph, neighbor.vertex, neighbor.color);
+        Ok(new_param)
+    }
+
+    fn apply_move(&self, param: &mut Coloring, neighbor: &Recolor) -> Result<(), LocalSearchError> {
+        param.recolor(&self.graph, neighbor.vertex, neighbor.color);
+        Ok(())
+    }
+
+    fn tabu_move(&self, param: &Coloring, neighbor: &Recolor) -> Recolor {
+        Recolor {
+            vertex: neighbor.vertex,
+            color: param.colors[neighbor.vertex],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graph, GraphColoring, Recolor};
+    use crate::errors::LocalSearchError;
+    use crate::problem::{CostFunction, Neighborhood};
+    use crate::solver::Solver;
+    use crate::{IterState, State};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const PENTAGON: &str = "c a 5-cycle\np edge 5 6\ne 1 2\ne 2 3\ne 3 4\ne 4 5\ne 5 1\ne 2 1\n";
+
+    #[test]
+    fn test_from_dimacs() {
+        let graph = Graph::from_dimacs(PENTAGON).unwrap();
+        assert_eq!(graph.num_vertices(), 5);
+        assert_eq!(graph.num_edges(), 5);
+        assert_eq!(graph.neighbors(0), &[1, 4]);
+    }
+
+    #[test]
+    fn test_from_dimacs_invalid() {
+        assert!(Graph::from_dimacs("e 1 2\n").is_err());
+        assert!(Graph::from_dimacs("p edge 2 1\ne 1 3\n").is_err());
+    }
+
+    #[test]
+    fn test_recolor_delta() {
+        let problem = GraphColoring::new(Graph::from_dimacs(PENTAGON).unwrap(), 3);
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = problem.coloring(vec![0, 0, 1, 1, 2]).unwrap();
+        assert_eq!(param.num_conflicts(), 2);
+        for neighbor in problem.get_neighbor_moves(&mut rng, &param).unwrap() {
+            let delta = problem.get_neighbor_delta(&param, &neighbor).unwrap();
+            let new_param = problem.make_move(&param, &neighbor).unwrap();
+            let new_cost = problem.cost(&new_param).unwrap();
+            assert_eq!(new_cost - problem.cost(&param).unwrap(), delta);
+            assert_eq!(new_param.num_conflicts() as f64, new_cost);
+            assert_eq!(
+                new_param,
+                problem.coloring(new_param.colors().to_vec()).unwrap()
+            );
+            let mut applied = param.clone();
+            problem.apply_move(&mut applied, &neighbor).unwrap();
+            assert_eq!(applied, new_param);
+        }
+    }
+
+    #[test]
+    fn test_invalid_coloring() {
+        let problem = GraphColoring::new(Graph::from_dimacs(PENTAGON).unwrap(), 3);
+        for colors in [vec![0, 1, 2, 0], vec![0, 1, 2, 0, 3]] {
+            assert!(matches!(
+                problem.coloring(colors),
+                Err(LocalSearchError::InvalidParam(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tabu_move() {
+        let problem = GraphColoring::new(Graph::from_dimacs(PENTAGON).unwrap(), 3);
+        let param = problem.coloring(vec![0, 0, 1, 1, 2]).unwrap();
+        let neighbor = Recolor {
+            vertex: 1,
+            color: 2,
+        };
+        assert_eq!(
+            problem.tabu_move(&param, &neighbor),
+            Recolor {
+                vertex: 1,
+                color: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_tabucol_moves() {
+        // K4 has no 3-coloring, every recolor of the optimum is non-improving
+        let mut graph = Graph::new(4);
+        for u in 0..4 {
+            for v in u + 1..4 {
+                graph.add_edge(u, v);
+            }
+        }
+        let mut problem = GraphColoring::new(graph, 3);
+        let mut solver = GraphColoring::tabucol(2, StdRng::seed_from_u64(0));
+        let param = problem.coloring(vec![0, 1, 2, 0]).unwrap();
+        let mut state = solver
+            .init(&mut problem, IterState::new().param(param))
+            .unwrap();
+        for _ in 0..20 {
+            let prev = state.get_param().unwrap().clone();
+            state = solver.next_iter(&mut problem, state).unwrap();
+            state.update();
+            let param = state.get_param().unwrap();
+            assert_ne!(param.colors(), prev.colors());
+            assert_eq!(param.num_conflicts() as f64, state.get_cost());
+        }
+    }
+}
@@ -0,0 +1,491 @@
+//! 0/1 and multidimensional knapsack: select items maximizing the total profit without
+//! exceeding any capacity. The cost is the negated profit plus a penalty proportional to
+//! the total overload, so that infeasible selections can be traversed. Without penalty, the
+//! capacities can be handled as `Constraints` by `crate::Penalized` instead.
+use super::{parse_token, read_instance};
+use crate::errors::LocalSearchError;
+use crate::problem::{Constraints, CostFunction, Neighborhood};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Knapsack {
+    profits: Vec<f64>,
+    /// `weights[k][i]` is the weight of item `i` in dimension `k`.
+    weights: Vec<Vec<f64>>,
+    capacities: Vec<f64>,
+    penalty: f64,
+    optimum: Option<f64>,
+    sample_size: Option<usize>,
+}
+
+/// Selected items with their loads, kept up to date by the moves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KnapsackSolution {
+    selected: Vec<bool>,
+    loads: Vec<f64>,
+    profit: f64,
+    overload: f64,
+}
+
+impl KnapsackSolution {
+    pub fn selected(&self) -> &[bool] {
+        &self.selected
+    }
+
+    pub fn loads(&self) -> &[f64] {
+        &self.loads
+    }
+
+    pub fn profit(&self) -> f64 {
+        self.profit
+    }
+
+    /// Total excess of the loads over the capacities.
+    pub fn overload(&self) -> f64 {
+        self.overload
+    }
+
+    pub fn is_feasible(&self) -> bool {
+        self.overload <= 0.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KnapsackMove {
+    Add(usize),
+    Drop(usize),
+    Swap { drop: usize, add: usize },
+}
+
+impl Knapsack {
+    /// The penalty per unit of overload defaults to twice the highest profit per unit of
+    /// weight among the items, so that overloading a capacity rarely pays off and an
+    /// overloaded selection never ties with the feasible one without the overload.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the dimensions of `weights` and `capacities` do not match `profits`.
+    pub fn new(profits: Vec<f64>, weights: Vec<Vec<f64>>, capacities: Vec<f64>) -> Self {
+        assert_eq!(weights.len(), capacities.len());
+        assert!(weights.iter().all(|row| row.len() == profits.len()));
+        let penalty = profits
+            .iter()
+            .enumerate()
+            .map(|(i, profit)| {
+                let weight = weights
+                    .iter()
+                    .map(|row| row[i])
+                    .fold(f64::INFINITY, f64::min);
+                if weight > 0.0 {
+                    profit / weight
+                } else {
+                    *profit
+                }
+            })
+            .fold(1.0, f64::max)
+            * 2.0;
+        Self {
+            profits,
+            weights,
+            capacities,
+            penalty,
+            optimum: None,
+            sample_size: None,
+        }
+    }
+
+    /// The classic single constraint 0/1 knapsack.
+    pub fn single(profits: Vec<f64>, weights: Vec<f64>, capacity: f64) -> Self {
+        Self::new(profits, vec![weights], vec![capacity])
+    }
+
+    #[must_use]
+    pub fn with_penalty(mut self, penalty: f64) -> Self {
+        self.penalty = penalty;
+        self
+    }
+
+    /// Only draw `sample_size` random moves per neighborhood instead of all add, drop and
+    /// swap moves.
+    #[must_use]
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.sample_size = Some(sample_size);
+        self
+    }
+
+    /// Parse every instance of a file in the OR-Library `mknap` format: the number of
+    /// instances, then for each one the number of items, of constraints and the optimal
+    /// profit (0 if unknown), the profits, the weights row by row and the capacities.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input is truncated.
+    pub fn from_orlib(input: &str) -> Result<Vec<Self>, LocalSearchError> {
+        let mut tokens = input.split_whitespace();
+        let count: usize = parse_token(tokens.next(), "number of instances")?;
+        let mut next = |what: &str| parse_token::<f64>(tokens.next(), what);
+        (0..count)
+            .map(|_| {
+                let n = next("number of items")? as usize;
+                let m = next("number of constraints")? as usize;
+                let optimum = next("optimal profit")?;
+                let profits = (0..n)
+                    .map(|_| next("profit"))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let weights = (0..m)
+                    .map(|_| (0..n).map(|_| next("weight")).collect())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let capacities = (0..m)
+                    .map(|_| next("capacity"))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut instance = Self::new(profits, weights, capacities);
+                instance.optimum = (optimum > 0.0).then_some(optimum);
+                Ok(instance)
+            })
+            .collect()
+    }
+
+    /// Read a file in the OR-Library `mknap` format.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed.
+    pub fn read_orlib<P: AsRef<Path>>(path: P) -> Result<Vec<Self>, LocalSearchError> {
+        Self::from_orlib(&read_instance(path)?)
+    }
+
+    pub fn num_items(&self) -> usize {
+        self.profits.len()
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.capacities.len()
+    }
+
+    /// Best known profit, when given by the instance file.
+    pub fn optimum(&self) -> Option<f64> {
+        self.optimum
+    }
+
+    fn overload(&self, loads: &[f64]) -> f64 {
+        loads
+            .iter()
+            .zip(&self.capacities)
+            .map(|(load, capacity)| (load - capacity).max(0.0))
+            .sum()
+    }
+
+    /// Compute the loads of a selection.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the selection does not have one entry per item.
+    pub fn solution(&self, selected: Vec<bool>) -> Result<KnapsackSolution, LocalSearchError> {
+        if selected.len() != self.num_items() {
+            return Err(LocalSearchError::InvalidParam(format!(
+                "{} selections for {} items",
+                selected.len(),
+                self.num_items()
+            )));
+        }
+        let loads: Vec<f64> = self
+            .weights
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&selected)
+                    .filter(|(_, s)| **s)
+                    .map(|(w, _)| w)
+                    .sum()
+            })
+            .collect();
+        let profit = self
+            .profits
+            .iter()
+            .zip(&selected)
+            .filter(|(_, s)| **s)
+            .map(|(p, _)| p)
+            .sum();
+        Ok(KnapsackSolution {
+            overload: self.overload(&loads),
+            selected,
+            loads,
+            profit,
+        })
+    }
+
+    /// Add items by decreasing profit over their weight relative to the capacities, as
+    /// long as they fit.
+    ///
+    /// # Errors
+    ///
+    /// Never fails, the `Result` matches the other constructors.
+    pub fn greedy(&self) -> Result<KnapsackSolution, LocalSearchError> {
+        let efficiency = |i: usize| {
+            let weight: f64 = self
+                .weights
+                .iter()
+                .zip(&self.capacities)
+                .map(|(row, capacity)| row[i] / capacity.max(f64::MIN_POSITIVE))
+                .sum();
+            self.profits[i] / weight.max(f64::MIN_POSITIVE)
+        };
+        let mut items: Vec<usize> = (0..self.num_items()).collect();
+        items.sort_by(|&a, &b| efficiency(b).total_cmp(&efficiency(a)));
+        let mut param = self.solution(vec![false; self.num_items()])?;
+        for item in items {
+            if self.loads_after(&param, &KnapsackMove::Add(item)).1 <= 0.0 {
+                param = self.make_move(&param, &KnapsackMove::Add(item))?;
+            }
+        }
+        Ok(param)
+    }
+
+    /// Add items in random order as long as they fit.
+    ///
+    /// # Errors
+    ///
+    /// Never fails, the `Result` matches the other constructors.
+    pub fn init_solution<R: Rng>(&self, rng: &mut R) -> Result<KnapsackSolution, LocalSearchError> {
+        let mut items: Vec<usize> = (0..self.num_items()).collect();
+        items.shuffle(rng);
+        let mut param = self.solution(vec![false; self.num_items()])?;
+        for item in items {
+            if self.loads_after(&param, &KnapsackMove::Add(item)).1 <= 0.0 {
+                param = self.make_move(&param, &KnapsackMove::Add(item))?;
+            }
+        }
+        Ok(param)
+    }
+
+    fn loads_after(&self, param: &KnapsackSolution, mv: &KnapsackMove) -> (Vec<f64>, f64) {
+        let mut loads = param.loads.clone();
+        let (added, dropped) = match *mv {
+            KnapsackMove::Add(i) => (Some(i), None),
+            KnapsackMove::Drop(i) => (None, Some(i)),
+            KnapsackMove::Swap { drop, add } => (Some(add), Some(drop)),
+        };
+        for (load, row) in loads.iter_mut().zip(&self.weights) {
+            *load += added.map_or(0.0, |i| row[i]) - dropped.map_or(0.0, |i| row[i]);
+        }
+        let overload = self.overload(&loads);
+        (loads, overload)
+    }
+
+    fn profit_delta(&self, mv: &KnapsackMove) -> f64 {
+        match *mv {
+            KnapsackMove::Add(i) => self.profits[i],
+            KnapsackMove::Drop(i) => -self.profits[i],
+            KnapsackMove::Swap { drop, add } => self.profits[add] - self.profits[drop],
+        }
+    }
+}
+
+impl CostFunction for Knapsack {
+    type Param = KnapsackSolution;
+
+    fn cost(&self, param: &KnapsackSolution) -> Result<f64, LocalSearchError> {
+        let full = self.solution(param.selected.clone())?;
+        Ok(-full.profit + self.penalty * full.overload)
+    }
+}
+
+impl Neighborhood for Knapsack {
+    type Param = KnapsackSolution;
+    type Neighbor = KnapsackMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<KnapsackSolution, LocalSearchError> {
+        self.init_solution(rng)
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &KnapsackSolution,
+    ) -> Result<Vec<KnapsackMove>, LocalSearchError> {
+        let (inside, outside): (Vec<usize>, Vec<usize>) =
+            (0..self.num_items()).partition(|&i| param.selected[i]);
+        let mut moves: Vec<KnapsackMove> = match self.sample_size {
+            Some(size) => (0..size)
+                .filter_map(|_| match rng.gen_range(0..3) {
+                    0 => outside.choose(rng).map(|&i| KnapsackMove::Add(i)),
+                    1 => inside.choose(rng).map(|&i| KnapsackMove::Drop(i)),
+                    _ => Some(KnapsackMove::Swap {
+                        drop: *inside.choose(rng)?,
+                        add: *outside.choose(rng)?,
+                    }),
+                })
+                .collect(),
+            _ => outside
+                .iter()
+                .map(|&i| KnapsackMove::Add(i))
+                .chain(inside.iter().map(|&i| KnapsackMove::Drop(i)))
+                .chain(inside.iter().flat_map(|&drop| {
+                    outside
+                        .iter()
+                        .map(move |&add| KnapsackMove::Swap { drop, add })
+                }))
+                .collect(),
+        };
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &KnapsackSolution,
+        neighbor: &KnapsackMove,
+    ) -> Result<f64, LocalSearchError> {
+        let (_, overload) = self.loads_after(param, neighbor);
+        Ok(-self.profit_delta(neighbor) + self.penalty * (overload - param.overload))
+    }
+
+    fn make_move(
+        &self,
+        param: &KnapsackSolution,
+        neighbor: &KnapsackMove,
+    ) -> Result<KnapsackSolution, LocalSearchError> {
+        let (loads, overload) = self.loads_after(param, neighbor);
+        let mut selected = param.selected.clone();
+        match *neighbor {
+            KnapsackMove::Add(i) => selected[i] = true,
+            KnapsackMove::Drop(i) => selected[i] = false,
+            KnapsackMove::Swap { drop, add } => {
+                selected[drop] = false;
+                selected[add] = true;
+            }
+        }
+        Ok(KnapsackSolution {
+            selected,
+            loads,
+            profit: param.profit + self.profit_delta(neighbor),
+            overload,
+        })
+    }
+}
+
+/// One constraint per capacity, violated by the excess of its load.
+impl Constraints for Knapsack {
+    type Param = KnapsackSolution;
+    type Neighbor = KnapsackMove;
+
+    fn num_constraints(&self) -> usize {
+        self.capacities.len()
+    }
+
+    fn violations(&self, param: &KnapsackSolution) -> Result<Vec<f64>, LocalSearchError> {
+        Ok(param
+            .loads
+            .iter()
+            .zip(&self.capacities)
+            .map(|(load, capacity)| (load - capacity).max(0.0))
+            .collect())
+    }
+
+    fn get_violation_deltas(
+        &self,
+        param: &KnapsackSolution,
+        neighbor: &KnapsackMove,
+    ) -> Result<Vec<f64>, LocalSearchError> {
+        let (loads, _) = self.loads_after(param, neighbor);
+        Ok(loads
+            .iter()
+            .zip(&param.loads)
+            .zip(&self.capacities)
+            .map(|((new, old), capacity)| (new - capacity).max(0.0) - (old - capacity).max(0.0))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Knapsack, KnapsackSolution};
+    use crate::errors::LocalSearchError;
+    use crate::problem::{Constraints, CostFunction, Neighborhood};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const MKNAP: &str = "2
+ 4 2 13
+ 5 4 3 6
+ 2 3 1 4
+ 3 1 2 2
+ 6 5
+ 3 1 0
+ 1 2 3
+ 1 1 1
+ 2
+";
+
+    #[test]
+    fn test_from_orlib() {
+        let instances = Knapsack::from_orlib(MKNAP).unwrap();
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[0].num_items(), 4);
+        assert_eq!(instances[0].num_constraints(), 2);
+        assert_eq!(instances[0].optimum(), Some(13.0));
+        assert_eq!(instances[1].optimum(), None);
+        assert!(Knapsack::from_orlib("1\n 3 1 0\n 1 2").is_err());
+        assert!(matches!(
+            instances[0].solution(vec![true; 3]),
+            Err(LocalSearchError::InvalidParam(_))
+        ));
+    }
+
+    #[test]
+    fn test_delta() {
+        let problem = Knapsack::from_orlib(MKNAP).unwrap().remove(0);
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = problem.greedy().unwrap();
+        assert!(param.is_feasible());
+        for neighbor in problem.get_neighbor_moves(&mut rng, &param).unwrap() {
+            let delta = problem.get_neighbor_delta(&param, &neighbor).unwrap();
+            let new_param = problem.make_move(&param, &neighbor).unwrap();
+            let expected = problem.cost(&new_param).unwrap() - problem.cost(&param).unwrap();
+            assert!((expected - delta).abs() < 1e-9);
+            assert_eq!(
+                new_param,
+                problem.solution(new_param.selected().to_vec()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_violation_deltas() {
+        let problem = Knapsack::from_orlib(MKNAP).unwrap().remove(0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let param = problem.solution(vec![true, true, false, true]).unwrap();
+        let violations = problem.violations(&param).unwrap();
+        assert_eq!(violations, vec![3.0, 1.0]);
+        for neighbor in problem.get_neighbor_moves(&mut rng, &param).unwrap() {
+            let deltas = problem.get_violation_deltas(&param, &neighbor).unwrap();
+            let new_param = problem.make_move(&param, &neighbor).unwrap();
+            let new_violations = problem.violations(&new_param).unwrap();
+            for k in 0..2 {
+                assert!((new_violations[k] - violations[k] - deltas[k]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_penalty() {
+        // Adding item 1 overloads the capacity by its whole weight
+        let problem = Knapsack::single(vec![10.0, 3.0], vec![10.0, 3.0], 10.0);
+        let feasible = problem.solution(vec![true, false]).unwrap();
+        let overloaded = problem.solution(vec![true, true]).unwrap();
+        assert!(problem.cost(&feasible).unwrap() < problem.cost(&overloaded).unwrap());
+    }
+
+    #[test]
+    fn test_random_param() {
+        let problem = Knapsack::from_orlib(MKNAP).unwrap().remove(0);
+        let mut rng = StdRng::seed_from_u64(0);
+        let params: Vec<KnapsackSolution> = (0..10)
+            .map(|_| problem.random_param(&mut rng).unwrap())
+            .collect();
+        assert!(params.iter().all(KnapsackSolution::is_feasible));
+        assert!(params.iter().any(|param| param != &params[0]));
+    }
+}
@@ -0,0 +1,23 @@
+//! Ready-made models of classic combinatorial problems, together with readers for the
+//! usual benchmark file formats.
+pub mod coloring;
+pub mod knapsack;
+pub mod scheduling;
+pub mod setcover;
+
+use crate::errors::LocalSearchError;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+fn read_instance<P: AsRef<Path>>(path: P) -> Result<String, LocalSearchError> {
+    fs::read_to_string(path.as_ref()).map_err(|err| {
+        LocalSearchError::FailReadInstance(format!("{}: {err}", path.as_ref().display()))
+    })
+}
+
+fn parse_token<T: FromStr>(token: Option<&str>, what: &str) -> Result<T, LocalSearchError> {
+    token
+        .and_then(|tok| tok.parse().ok())
+        .ok_or_else(|| LocalSearchError::FailReadInstance(format!("invalid or missing {what}")))
+}
@@ -0,0 +1,1016 @@
+//! Makespan minimization for the permutation flow shop and the job shop.
+//!
+//! Both models keep the heads (earliest completion or start times) and tails (longest
+//! path to the end of the schedule) of the current solution, so that a move only needs
+//! to recompute the part of the schedule it rearranges.
+use super::{parse_token, read_instance};
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood};
+use rand::seq::{index, SliceRandom};
+use rand::Rng;
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Lines made only of numbers, which is what remains of the benchmark files once their
+/// textual headers are removed.
+fn numeric_lines(input: &str) -> Vec<Vec<&str>> {
+    input
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|tokens| !tokens.is_empty() && tokens.iter().all(|tok| tok.parse::<f64>().is_ok()))
+        .collect()
+}
+
+fn parse_row(
+    tokens: Option<&Vec<&str>>,
+    len: usize,
+    what: &str,
+) -> Result<Vec<f64>, LocalSearchError> {
+    let tokens = tokens.filter(|tokens| tokens.len() >= len).ok_or_else(|| {
+        LocalSearchError::FailReadInstance(format!("expected {len} values for {what}"))
+    })?;
+    tokens[..len]
+        .iter()
+        .map(|tok| parse_token(Some(tok), what))
+        .collect()
+}
+
+/// Permutation flow shop: every job is processed on machines `0..m` in order, and all
+/// machines process the jobs in the same order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowShop {
+    /// `times[job][machine]`
+    times: Vec<Vec<f64>>,
+    num_machines: usize,
+    sample_size: Option<usize>,
+}
+
+/// A job order with its heads and tails. `heads[i * m + k]` is the completion time of the
+/// job at position `i` on machine `k`, `tails[i * m + k]` the length of the longest path
+/// from the start of that operation to the end of the schedule.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowShopSequence {
+    order: Vec<usize>,
+    heads: Vec<f64>,
+    tails: Vec<f64>,
+}
+
+impl FlowShopSequence {
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn makespan(&self) -> f64 {
+        self.heads.last().copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlowShopMove {
+    /// Remove the job at position `from` and reinsert it so that it ends up at `to`.
+    Insert { from: usize, to: usize },
+    /// Exchange the jobs at positions `i` and `j`.
+    Swap { i: usize, j: usize },
+}
+
+impl FlowShopMove {
+    /// Positions `a..=b` rearranged by the move and their new content.
+    fn segment(&self, order: &[usize]) -> (usize, usize, Vec<usize>) {
+        match *self {
+            Self::Insert { from, to } if from <= to => {
+                let mut seg = order[from + 1..=to].to_vec();
+                seg.push(order[from]);
+                (from, to, seg)
+            }
+            Self::Insert { from, to } => {
+                let mut seg = vec![order[from]];
+                seg.extend_from_slice(&order[to..from]);
+                (to, from, seg)
+            }
+            Self::Swap { i, j } => {
+                let (a, b) = (i.min(j), i.max(j));
+                let mut seg = order[a..=b].to_vec();
+                let last = seg.len() - 1;
+                seg.swap(0, last);
+                (a, b, seg)
+            }
+        }
+    }
+}
+
+impl FlowShop {
+    /// # Panics
+    ///
+    /// Panic if jobs do not have the same number of machines.
+    pub fn new(times: Vec<Vec<f64>>) -> Self {
+        let num_machines = times.first().map_or(0, Vec::len);
+        assert!(times.iter().all(|job| job.len() == num_machines));
+        Self {
+            times,
+            num_machines,
+            sample_size: None,
+        }
+    }
+
+    /// Only draw `sample_size` random moves per neighborhood instead of all O(n²) of them.
+    #[must_use]
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.sample_size = Some(sample_size);
+        self
+    }
+
+    /// Parse every instance of a file in Taillard's format: a header line with the number
+    /// of jobs and machines (followed by the seed and bounds), then one line of processing
+    /// times per machine.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input is truncated or contains no instance.
+    pub fn from_taillard(input: &str) -> Result<Vec<Self>, LocalSearchError> {
+        let lines = numeric_lines(input);
+        let mut lines = lines.iter();
+        let mut instances = Vec::new();
+        while let Some(header) = lines.next() {
+            let num_jobs: usize = parse_token(header.first().copied(), "number of jobs")?;
+            let num_machines: usize = parse_token(header.get(1).copied(), "number of machines")?;
+            let rows = (0..num_machines)
+                .map(|_| parse_row(lines.next(), num_jobs, "processing times"))
+                .collect::<Result<Vec<_>, _>>()?;
+            let times = (0..num_jobs)
+                .map(|job| rows.iter().map(|row| row[job]).collect())
+                .collect();
+            instances.push(Self::new(times));
+        }
+        if instances.is_empty() {
+            return Err(LocalSearchError::FailReadInstance(
+                "no instance found".to_owned(),
+            ));
+        }
+        Ok(instances)
+    }
+
+    /// Read a file in Taillard's flow shop format.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed.
+    pub fn read_taillard<P: AsRef<Path>>(path: P) -> Result<Vec<Self>, LocalSearchError> {
+        Self::from_taillard(&read_instance(path)?)
+    }
+
+    pub fn num_jobs(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn num_machines(&self) -> usize {
+        self.num_machines
+    }
+
+    /// Completion times on every machine after appending `jobs` to a partial schedule
+    /// whose last completion times are `prev`.
+    fn forward(
+        &self,
+        prev: &mut [f64],
+        jobs: impl Iterator<Item = usize>,
+        mut out: Option<&mut [f64]>,
+    ) {
+        for (i, job) in jobs.enumerate() {
+            let mut last = 0.0_f64;
+            for (k, time) in self.times[job].iter().enumerate() {
+                last = prev[k].max(last) + time;
+                prev[k] = last;
+            }
+            if let Some(out) = out.as_deref_mut() {
+                out[i * self.num_machines..(i + 1) * self.num_machines].copy_from_slice(prev);
+            }
+        }
+    }
+
+    fn tails(&self, order: &[usize]) -> Vec<f64> {
+        let m = self.num_machines;
+        let mut tails: Vec<f64> = vec![0.0; order.len() * m];
+        for i in (0..order.len()).rev() {
+            for k in (0..m).rev() {
+                let below = if i + 1 < order.len() {
+                    tails[(i + 1) * m + k]
+                } else {
+                    0.0
+                };
+                let right = if k + 1 < m { tails[i * m + k + 1] } else { 0.0 };
+                tails[i * m + k] = below.max(right) + self.times[order[i]][k];
+            }
+        }
+        tails
+    }
+
+    /// Compute the heads and tails of a job order.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `order` is not a permutation of the jobs.
+    pub fn sequence(&self, order: Vec<usize>) -> Result<FlowShopSequence, LocalSearchError> {
+        let mut seen = vec![false; self.num_jobs()];
+        if order.len() != seen.len()
+            || !order
+                .iter()
+                .all(|&job| job < seen.len() && !std::mem::replace(&mut seen[job], true))
+        {
+            return Err(LocalSearchError::InvalidParam(
+                "the order is not a permutation of the jobs".to_owned(),
+            ));
+        }
+        let mut heads = vec![0.0; order.len() * self.num_machines];
+        self.forward(
+            &mut vec![0.0; self.num_machines],
+            order.iter().copied(),
+            Some(&mut heads),
+        );
+        let tails = self.tails(&order);
+        Ok(FlowShopSequence {
+            order,
+            heads,
+            tails,
+        })
+    }
+
+    /// Draw a uniformly random job order.
+    ///
+    /// # Errors
+    ///
+    /// Never fails, the `Result` matches the other constructors.
+    pub fn init_solution<R: Rng>(&self, rng: &mut R) -> Result<FlowShopSequence, LocalSearchError> {
+        let mut order: Vec<usize> = (0..self.num_jobs()).collect();
+        order.shuffle(rng);
+        self.sequence(order)
+    }
+
+    /// The NEH heuristic: insert the jobs by decreasing total processing time at the
+    /// position minimizing the partial makespan, using Taillard's acceleration.
+    ///
+    /// # Errors
+    ///
+    /// Never fails, the `Result` matches the other constructors.
+    pub fn neh(&self) -> Result<FlowShopSequence, LocalSearchError> {
+        let m = self.num_machines;
+        let mut jobs: Vec<usize> = (0..self.num_jobs()).collect();
+        jobs.sort_by(|&a, &b| {
+            let total = |job: usize| self.times[job].iter().sum::<f64>();
+            total(b).total_cmp(&total(a))
+        });
+        let mut order: Vec<usize> = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            let mut heads = vec![0.0; order.len() * m];
+            self.forward(&mut vec![0.0; m], order.iter().copied(), Some(&mut heads));
+            let tails = self.tails(&order);
+            let (mut best_pos, mut best_makespan) = (0, f64::INFINITY);
+            for pos in 0..=order.len() {
+                let mut completion = if pos == 0 {
+                    vec![0.0; m]
+                } else {
+                    heads[(pos - 1) * m..pos * m].to_vec()
+                };
+                self.forward(&mut completion, std::iter::once(job), None);
+                let makespan = if pos < order.len() {
+                    (0..m)
+                        .map(|k| completion[k] + tails[pos * m + k])
+                        .fold(0.0, f64::max)
+                } else {
+                    completion[m - 1]
+                };
+                if makespan < best_makespan {
+                    (best_pos, best_makespan) = (pos, makespan);
+                }
+            }
+            order.insert(best_pos, job);
+        }
+        self.sequence(order)
+    }
+}
+
+impl CostFunction for FlowShop {
+    type Param = FlowShopSequence;
+
+    fn cost(&self, param: &FlowShopSequence) -> Result<f64, LocalSearchError> {
+        let mut completion = vec![0.0; self.num_machines];
+        self.forward(&mut completion, param.order.iter().copied(), None);
+        Ok(completion.last().copied().unwrap_or(0.0))
+    }
+}
+
+impl Neighborhood for FlowShop {
+    type Param = FlowShopSequence;
+    type Neighbor = FlowShopMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<FlowShopSequence, LocalSearchError> {
+        self.init_solution(rng)
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &FlowShopSequence,
+    ) -> Result<Vec<FlowShopMove>, LocalSearchError> {
+        let n = param.order.len();
+        if n < 2 {
+            return Ok(Vec::new());
+        }
+        let mut moves: Vec<FlowShopMove> = match self.sample_size {
+            Some(size) => (0..size)
+                .map(|_| {
+                    let pair = index::sample(rng, n, 2);
+                    let (from, to) = (pair.index(0), pair.index(1));
+                    if rng.gen() {
+                        FlowShopMove::Insert { from, to }
+                    } else {
+                        FlowShopMove::Swap {
+                            i: from.min(to),
+                            j: from.max(to),
+                        }
+                    }
+                })
+                .collect(),
+            None => (0..n)
+                .flat_map(|from| {
+                    (0..n).filter(move |&to| to != from).flat_map(move |to| {
+                        let swap = (from < to).then_some(FlowShopMove::Swap { i: from, j: to });
+                        // Inserting right after the next job is the same as swapping them
+                        let insert = (to + 1 != from).then_some(FlowShopMove::Insert { from, to });
+                        swap.into_iter().chain(insert)
+                    })
+                })
+                .collect(),
+        };
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &FlowShopSequence,
+        neighbor: &FlowShopMove,
+    ) -> Result<f64, LocalSearchError> {
+        let m = self.num_machines;
+        let (a, b, seg) = neighbor.segment(&param.order);
+        let mut completion = if a == 0 {
+            vec![0.0; m]
+        } else {
+            param.heads[(a - 1) * m..a * m].to_vec()
+        };
+        self.forward(&mut completion, seg.into_iter(), None);
+        let makespan = if b + 1 < param.order.len() {
+            (0..m)
+                .map(|k| completion[k] + param.tails[(b + 1) * m + k])
+                .fold(0.0, f64::max)
+        } else {
+            completion[m - 1]
+        };
+        Ok(makespan - param.makespan())
+    }
+
+    fn make_move(
+        &self,
+        param: &FlowShopSequence,
+        neighbor: &FlowShopMove,
+    ) -> Result<FlowShopSequence, LocalSearchError> {
+        let (a, b, seg) = neighbor.segment(&param.order);
+        let mut order = param.order.clone();
+        order[a..=b].copy_from_slice(&seg);
+        self.sequence(order)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Operation {
+    pub job: usize,
+    pub machine: usize,
+    pub duration: f64,
+}
+
+/// Neighborhoods of the job shop, both restricted to the critical blocks: maximal
+/// sequences of consecutive operations of a critical path processed on the same machine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum JobShopNeighborhood {
+    /// Nowicki and Smutnicki: swap the first two and the last two operations of every
+    /// block, except the first two of the first block and the last two of the last one.
+    #[default]
+    N5,
+    /// Zhang et al.: N5 plus moving an inner operation to the front or the back of its
+    /// block, and the first or last operation inside the block, when provably feasible.
+    N7,
+}
+
+/// Move the operation at index `from` of the sequence of `machine` to index `to`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct JobShopMove {
+    pub machine: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Job shop in its disjunctive graph representation: every job is a chain of operations,
+/// one per machine, and a solution orders the operations on every machine.
+///
+/// Operation `job * m + i` is the `i`-th operation of `job`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobShop {
+    operations: Vec<Operation>,
+    num_jobs: usize,
+    num_machines: usize,
+    neighborhood: JobShopNeighborhood,
+    exact_delta: bool,
+}
+
+/// Machine sequences with the heads (earliest start times) and tails (longest path from
+/// the completion of an operation to the end) of the semi-active schedule they induce.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JobShopSchedule {
+    sequences: Vec<Vec<usize>>,
+    heads: Vec<f64>,
+    tails: Vec<f64>,
+    makespan: f64,
+}
+
+impl JobShopSchedule {
+    pub fn sequences(&self) -> &[Vec<usize>] {
+        &self.sequences
+    }
+
+    /// Start time of every operation.
+    pub fn start_times(&self) -> &[f64] {
+        &self.heads
+    }
+
+    pub fn makespan(&self) -> f64 {
+        self.makespan
+    }
+}
+
+impl JobShop {
+    /// `routes[job]` lists the `(machine, duration)` of the operations of `job` in order.
+    ///
+    /// # Panics
+    ///
+    /// Panic if jobs do not visit every machine exactly once.
+    pub fn new(routes: &[Vec<(usize, f64)>]) -> Self {
+        let num_machines = routes.first().map_or(0, Vec::len);
+        let operations: Vec<Operation> = routes
+            .iter()
+            .enumerate()
+            .flat_map(|(job, route)| {
+                let mut visited = vec![false; num_machines];
+                assert!(route.len() == num_machines);
+                route.iter().map(move |&(machine, duration)| {
+                    assert!(!std::mem::replace(&mut visited[machine], true));
+                    Operation {
+                        job,
+                        machine,
+                        duration,
+                    }
+                })
+            })
+            .collect();
+        Self {
+            operations,
+            num_jobs: routes.len(),
+            num_machines,
+            neighborhood: JobShopNeighborhood::default(),
+            exact_delta: true,
+        }
+    }
+
+    #[must_use]
+    pub fn with_neighborhood(mut self, neighborhood: JobShopNeighborhood) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    /// By default the delta of a move recomputes the schedule. Without `exact_delta` it is
+    /// the head/tail estimate of the longest path through the moved operations, a lower
+    /// bound of the new makespan: the costs the solvers track from deltas then drift, see
+    /// `crate::Executor::with_resync`.
+    #[must_use]
+    pub fn with_exact_delta(mut self, exact_delta: bool) -> Self {
+        self.exact_delta = exact_delta;
+        self
+    }
+
+    /// Parse an instance in the OR-Library format: a line with the number of jobs and
+    /// machines, then one line per job of `machine duration` pairs, machines from 0.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input is truncated, a machine is out of range or a job
+    /// does not visit every machine once.
+    pub fn from_orlib(input: &str) -> Result<Self, LocalSearchError> {
+        let lines = numeric_lines(input);
+        let mut lines = lines.iter().skip_while(|tokens| tokens.len() != 2);
+        let header = lines.next();
+        let num_jobs: usize = parse_token(header.map(|h| h[0]), "number of jobs")?;
+        let num_machines: usize = parse_token(header.map(|h| h[1]), "number of machines")?;
+        let routes = (0..num_jobs)
+            .map(|_| {
+                let row = parse_row(lines.next(), 2 * num_machines, "job route")?;
+                row.chunks(2)
+                    .map(|pair| {
+                        let machine = pair[0];
+                        if machine < 0.0 || machine >= num_machines as f64 || machine.fract() != 0.0
+                        {
+                            return Err(LocalSearchError::FailReadInstance(format!(
+                                "machine {machine} out of range 0..{num_machines}"
+                            )));
+                        }
+                        Ok((machine as usize, pair[1]))
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, LocalSearchError>>()?;
+        Self::checked(&routes)
+    }
+
+    /// Parse an instance in Taillard's format: a header line with the number of jobs and
+    /// machines, then one line of processing times per job, then one line of machines
+    /// (numbered from 1) per job.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input is truncated or a machine is out of range.
+    pub fn from_taillard(input: &str) -> Result<Self, LocalSearchError> {
+        let lines = numeric_lines(input);
+        let mut lines = lines.iter();
+        let header = lines.next();
+        let num_jobs: usize = parse_token(header.map(|h| h[0]), "number of jobs")?;
+        let num_machines: usize =
+            parse_token(header.and_then(|h| h.get(1).copied()), "number of machines")?;
+        let times = (0..num_jobs)
+            .map(|_| parse_row(lines.next(), num_machines, "processing times"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let routes = times
+            .into_iter()
+            .map(|times| {
+                let machines = parse_row(lines.next(), num_machines, "machines")?;
+                machines
+                    .into_iter()
+                    .zip(times)
+                    .map(|(machine, time)| {
+                        if machine < 1.0 || machine > num_machines as f64 || machine.fract() != 0.0
+                        {
+                            return Err(LocalSearchError::FailReadInstance(format!(
+                                "machine {machine} out of range 1..={num_machines}"
+                            )));
+                        }
+                        Ok((machine as usize - 1, time))
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, LocalSearchError>>()?;
+        Self::checked(&routes)
+    }
+
+    /// Read a file in the OR-Library job shop format.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed.
+    pub fn read_orlib<P: AsRef<Path>>(path: P) -> Result<Self, LocalSearchError> {
+        Self::from_orlib(&read_instance(path)?)
+    }
+
+    /// Read a file in Taillard's job shop format.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed.
+    pub fn read_taillard<P: AsRef<Path>>(path: P) -> Result<Self, LocalSearchError> {
+        Self::from_taillard(&read_instance(path)?)
+    }
+
+    fn checked(routes: &[Vec<(usize, f64)>]) -> Result<Self, LocalSearchError> {
+        let m = routes.first().map_or(0, Vec::len);
+        let valid = routes.iter().all(|route| {
+            let mut visited = vec![false; m];
+            route.len() == m
+                && route.iter().all(|&(machine, _)| {
+                    machine < m && !std::mem::replace(&mut visited[machine], true)
+                })
+        });
+        if !valid {
+            return Err(LocalSearchError::FailReadInstance(
+                "every job must visit every machine once".to_owned(),
+            ));
+        }
+        Ok(Self::new(routes))
+    }
+
+    pub fn num_jobs(&self) -> usize {
+        self.num_jobs
+    }
+
+    pub fn num_machines(&self) -> usize {
+        self.num_machines
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    fn job_pred(&self, op: usize) -> Option<usize> {
+        (!op.is_multiple_of(self.num_machines)).then(|| op - 1)
+    }
+
+    fn job_succ(&self, op: usize) -> Option<usize> {
+        (op % self.num_machines + 1 != self.num_machines).then(|| op + 1)
+    }
+
+    fn duration(&self, op: usize) -> f64 {
+        self.operations[op].duration
+    }
+
+    /// Compute the heads and tails of machine sequences.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the sequences are not permutations of the operations of each
+    /// machine or if they contain a cycle.
+    pub fn schedule(
+        &self,
+        sequences: Vec<Vec<usize>>,
+    ) -> Result<JobShopSchedule, LocalSearchError> {
+        let n = self.operations.len();
+        let valid = sequences.len() == self.num_machines
+            && sequences.iter().map(Vec::len).sum::<usize>() == n
+            && sequences.iter().enumerate().all(|(machine, seq)| {
+                seq.iter()
+                    .all(|&op| op < n && self.operations[op].machine == machine)
+            });
+        if !valid {
+            return Err(LocalSearchError::InvalidParam(
+                "the sequences do not hold the operations of each machine".to_owned(),
+            ));
+        }
+        let mut machine_pred = vec![None; n];
+        let mut machine_succ = vec![None; n];
+        for seq in &sequences {
+            for pair in seq.windows(2) {
+                machine_succ[pair[0]] = Some(pair[1]);
+                machine_pred[pair[1]] = Some(pair[0]);
+            }
+        }
+        let mut indegree: Vec<usize> = (0..n)
+            .map(|op| {
+                usize::from(self.job_pred(op).is_some()) + usize::from(machine_pred[op].is_some())
+            })
+            .collect();
+        let mut queue: VecDeque<usize> = (0..n).filter(|&op| indegree[op] == 0).collect();
+        let mut topological = Vec::with_capacity(n);
+        while let Some(op) = queue.pop_front() {
+            topological.push(op);
+            for succ in [self.job_succ(op), machine_succ[op]].into_iter().flatten() {
+                indegree[succ] -= 1;
+                if indegree[succ] == 0 {
+                    queue.push_back(succ);
+                }
+            }
+        }
+        if topological.len() != n {
+            return Err(LocalSearchError::InvalidParam(
+                "the machine sequences contain a cycle".to_owned(),
+            ));
+        }
+        let mut heads = vec![0.0; n];
+        for &op in &topological {
+            heads[op] = [self.job_pred(op), machine_pred[op]]
+                .into_iter()
+                .flatten()
+                .map(|pred| heads[pred] + self.duration(pred))
+                .fold(0.0, f64::max);
+        }
+        let mut tails = vec![0.0; n];
+        for &op in topological.iter().rev() {
+            tails[op] = [self.job_succ(op), machine_succ[op]]
+                .into_iter()
+                .flatten()
+                .map(|succ| tails[succ] + self.duration(succ))
+                .fold(0.0, f64::max);
+        }
+        let makespan = (0..n)
+            .map(|op| heads[op] + self.duration(op))
+            .fold(0.0, f64::max);
+        Ok(JobShopSchedule {
+            sequences,
+            heads,
+            tails,
+            makespan,
+        })
+    }
+
+    /// Build a random semi-active schedule by repeatedly appending the next operation of
+    /// a random unfinished job to its machine.
+    ///
+    /// # Errors
+    ///
+    /// Never fails, the `Result` matches the other constructors.
+    pub fn init_solution<R: Rng>(&self, rng: &mut R) -> Result<JobShopSchedule, LocalSearchError> {
+        let m = self.num_machines;
+        let mut next = vec![0; self.num_jobs];
+        let mut unfinished: Vec<usize> = (0..self.num_jobs).filter(|_| m > 0).collect();
+        let mut sequences = vec![Vec::with_capacity(self.num_jobs); m];
+        while !unfinished.is_empty() {
+            let pick = rng.gen_range(0..unfinished.len());
+            let job = unfinished[pick];
+            let op = job * m + next[job];
+            sequences[self.operations[op].machine].push(op);
+            next[job] += 1;
+            if next[job] == m {
+                unfinished.swap_remove(pick);
+            }
+        }
+        self.schedule(sequences)
+    }
+
+    fn is_critical(&self, param: &JobShopSchedule, op: usize) -> bool {
+        (param.heads[op] + self.duration(op) + param.tails[op] - param.makespan).abs() < 1e-9
+    }
+
+    /// Critical blocks of one critical path, as `(machine, first index, last index)` in
+    /// the machine sequences.
+    fn critical_blocks(&self, param: &JobShopSchedule) -> Vec<(usize, usize, usize)> {
+        let n = self.operations.len();
+        let mut position = vec![0; n];
+        for seq in &param.sequences {
+            for (i, &op) in seq.iter().enumerate() {
+                position[op] = i;
+            }
+        }
+        let Some(mut op) = (0..n).find(|&op| param.heads[op] == 0.0 && self.is_critical(param, op))
+        else {
+            return Vec::new();
+        };
+        let mut blocks: Vec<(usize, usize, usize)> = Vec::new();
+        loop {
+            let machine = self.operations[op].machine;
+            match blocks.last_mut() {
+                Some(block) if block.0 == machine && block.2 + 1 == position[op] => block.2 += 1,
+                _ => blocks.push((machine, position[op], position[op])),
+            }
+            let end = param.heads[op] + self.duration(op);
+            let machine_succ = param.sequences[machine].get(position[op] + 1).copied();
+            let next = [machine_succ, self.job_succ(op)]
+                .into_iter()
+                .flatten()
+                .find(|&succ| {
+                    (param.heads[succ] - end).abs() < 1e-9 && self.is_critical(param, succ)
+                });
+            match next {
+                Some(succ) => op = succ,
+                None => break,
+            }
+        }
+        blocks
+    }
+
+    fn job_head(&self, param: &JobShopSchedule, op: usize) -> f64 {
+        self.job_pred(op)
+            .map_or(0.0, |pred| param.heads[pred] + self.duration(pred))
+    }
+
+    fn job_tail(&self, param: &JobShopSchedule, op: usize) -> f64 {
+        self.job_succ(op)
+            .map_or(0.0, |succ| param.tails[succ] + self.duration(succ))
+    }
+
+    /// Balas and Vazacopoulos' sufficient conditions for a shift to keep the graph acyclic.
+    fn is_feasible(&self, param: &JobShopSchedule, mv: &JobShopMove) -> bool {
+        let seq = &param.sequences[mv.machine];
+        let (u, v) = (seq[mv.from], seq[mv.to]);
+        if mv.from.abs_diff(mv.to) == 1 {
+            return true;
+        }
+        if mv.from < mv.to {
+            // `u` moves right after `v`
+            param.tails[v] + self.duration(v) >= self.job_tail(param, u)
+        } else {
+            // `u` moves right before `v`
+            param.heads[v] + self.duration(v) >= self.job_head(param, u)
+        }
+    }
+
+    /// Head/tail estimate of the longest path through the operations rearranged by `mv`.
+    fn estimate(&self, param: &JobShopSchedule, mv: &JobShopMove) -> f64 {
+        let seq = &param.sequences[mv.machine];
+        let (a, b) = (mv.from.min(mv.to), mv.from.max(mv.to));
+        let mut seg = seq[a..=b].to_vec();
+        if mv.from < mv.to {
+            seg.rotate_left(1);
+        } else {
+            seg.rotate_right(1);
+        }
+        let mut heads = vec![0.0; seg.len()];
+        let mut prev = a
+            .checked_sub(1)
+            .map_or(0.0, |i| param.heads[seq[i]] + self.duration(seq[i]));
+        for (i, &op) in seg.iter().enumerate() {
+            heads[i] = prev.max(self.job_head(param, op));
+            prev = heads[i] + self.duration(op);
+        }
+        let mut next = seq
+            .get(b + 1)
+            .map_or(0.0, |&op| param.tails[op] + self.duration(op));
+        let mut longest = 0.0_f64;
+        for (i, &op) in seg.iter().enumerate().rev() {
+            let tail = next.max(self.job_tail(param, op));
+            longest = longest.max(heads[i] + self.duration(op) + tail);
+            next = tail + self.duration(op);
+        }
+        longest
+    }
+}
+
+impl CostFunction for JobShop {
+    type Param = JobShopSchedule;
+
+    fn cost(&self, param: &JobShopSchedule) -> Result<f64, LocalSearchError> {
+        Ok(self.schedule(param.sequences.clone())?.makespan)
+    }
+}
+
+impl Neighborhood for JobShop {
+    type Param = JobShopSchedule;
+    type Neighbor = JobShopMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<JobShopSchedule, LocalSearchError> {
+        self.init_solution(rng)
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &JobShopSchedule,
+    ) -> Result<Vec<JobShopMove>, LocalSearchError> {
+        let blocks = self.critical_blocks(param);
+        let last = blocks.len().saturating_sub(1);
+        let mut moves = Vec::new();
+        for (i, &(machine, first, end)) in blocks.iter().enumerate() {
+            if first == end {
+                continue;
+            }
+            let shift = |from, to| JobShopMove { machine, from, to };
+            if i > 0 {
+                moves.push(shift(first, first + 1));
+            }
+            if i < last && (i == 0 || end > first + 1) {
+                moves.push(shift(end - 1, end));
+            }
+            if self.neighborhood == JobShopNeighborhood::N7 {
+                for inner in first + 1..end {
+                    moves.extend(
+                        [
+                            shift(inner, first),
+                            shift(inner, end),
+                            shift(first, inner),
+                            shift(end, inner),
+                        ]
+                        .into_iter()
+                        .filter(|mv| mv.from.abs_diff(mv.to) > 1),
+                    );
+                }
+            }
+        }
+        moves.retain(|mv| self.is_feasible(param, mv));
+        moves.dedup();
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &JobShopSchedule,
+        neighbor: &JobShopMove,
+    ) -> Result<f64, LocalSearchError> {
+        if self.exact_delta {
+            return Ok(self.make_move(param, neighbor)?.makespan - param.makespan);
+        }
+        Ok(self.estimate(param, neighbor) - param.makespan)
+    }
+
+    fn make_move(
+        &self,
+        param: &JobShopSchedule,
+        neighbor: &JobShopMove,
+    ) -> Result<JobShopSchedule, LocalSearchError> {
+        let mut sequences = param.sequences.clone();
+        let seq = &mut sequences[neighbor.machine];
+        let op = seq.remove(neighbor.from);
+        seq.insert(neighbor.to, op);
+        self.schedule(sequences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlowShop, JobShop, JobShopNeighborhood};
+    use crate::errors::LocalSearchError;
+    use crate::problem::{CostFunction, Neighborhood};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const TAILLARD_FLOW_SHOP: &str =
+        "number of jobs, number of machines, initial seed, upper bound and lower bound :
+          4           3   873654221        1278        1232
+processing times :
+  5  3  8  2
+  4  6  1  7
+  3  2  9  4
+";
+
+    const FT06: &str = " instance ft06
+ +++++++++++++++++++++++++++++
+ Fisher and Thompson 6x6 instance, alternate name (mt06)
+ 6 6
+ 2  1  0  3  1  6  3  7  5  3  4  6
+ 1  8  2  5  4 10  5 10  0 10  3  4
+ 2  5  3  4  5  8  0  9  1  1  4  7
+ 1  5  0  5  2  5  3  3  4  8  5  9
+ 2  9  1  3  4  5  5  4  0  3  3  1
+ 1  3  3  3  5  9  0 10  4  4  2  1
+ +++++++++++++++++++++++++++++
+";
+
+    #[test]
+    fn test_flow_shop_from_taillard() {
+        let instances = FlowShop::from_taillard(TAILLARD_FLOW_SHOP).unwrap();
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].num_jobs(), 4);
+        assert_eq!(instances[0].num_machines(), 3);
+        let param = instances[0].sequence(vec![0, 1, 2, 3]).unwrap();
+        assert_eq!(param.makespan(), 30.0);
+        assert_eq!(instances[0].cost(&param).unwrap(), 30.0);
+        assert!(matches!(
+            instances[0].sequence(vec![0, 1, 1, 3]),
+            Err(LocalSearchError::InvalidParam(_))
+        ));
+    }
+
+    #[test]
+    fn test_flow_shop_delta() {
+        let problem = FlowShop::from_taillard(TAILLARD_FLOW_SHOP)
+            .unwrap()
+            .remove(0);
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = problem.neh().unwrap();
+        assert!(param.makespan() <= 30.0);
+        for neighbor in problem.get_neighbor_moves(&mut rng, &param).unwrap() {
+            let delta = problem.get_neighbor_delta(&param, &neighbor).unwrap();
+            let new_param = problem.make_move(&param, &neighbor).unwrap();
+            assert_eq!(problem.cost(&new_param).unwrap() - param.makespan(), delta);
+        }
+    }
+
+    #[test]
+    fn test_job_shop_from_orlib() {
+        let problem = JobShop::from_orlib(FT06).unwrap();
+        assert_eq!(problem.num_jobs(), 6);
+        assert_eq!(problem.num_machines(), 6);
+        assert_eq!(problem.operations()[1].machine, 0);
+        assert_eq!(problem.operations()[1].duration, 3.0);
+        assert!(JobShop::from_orlib(" 2 2\n 0 1 1 2\n").is_err());
+        assert!(JobShop::from_orlib(" 2 2\n 1.5 1 0 2\n 1 3 0 4\n").is_err());
+        assert!(JobShop::from_orlib(" 2 2\n 1 1 -0.5 2\n 1 3 0 4\n").is_err());
+        assert!(JobShop::from_orlib(" 2 2\n 0 1 1 2\n 1 3 0 4\n").is_ok());
+    }
+
+    #[test]
+    fn test_job_shop_from_taillard() {
+        let problem = JobShop::from_taillard("2 2\n3 4\n5 6\n1 2\n2 1\n").unwrap();
+        assert_eq!(problem.operations()[1].machine, 1);
+        assert_eq!(problem.operations()[2].duration, 5.0);
+        assert!(problem.schedule(vec![vec![0, 3], vec![2, 1]]).is_ok());
+        for sequences in [vec![vec![0, 1], vec![2, 3]], vec![vec![3, 0], vec![1, 2]]] {
+            assert!(matches!(
+                problem.schedule(sequences),
+                Err(LocalSearchError::InvalidParam(_))
+            ));
+        }
+        assert!(JobShop::from_taillard("2 2\n3 4\n5 6\n0 1\n2 1\n").is_err());
+        assert!(JobShop::from_taillard("2 2\n3 4\n5 6\n1 3\n2 1\n").is_err());
+        assert!(JobShop::from_taillard("2 2\n3 4\n5 6\n-1 2\n2 1\n").is_err());
+    }
+
+    #[test]
+    fn test_job_shop_moves() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for neighborhood in [JobShopNeighborhood::N5, JobShopNeighborhood::N7] {
+            let problem = JobShop::from_orlib(FT06)
+                .unwrap()
+                .with_neighborhood(neighborhood)
+                .with_exact_delta(false);
+            let param = problem.init_solution(&mut rng).unwrap();
+            let moves = problem.get_neighbor_moves(&mut rng, &param).unwrap();
+            assert!(!moves.is_empty());
+            for neighbor in moves {
+                let new_param = problem.make_move(&param, &neighbor).unwrap();
+                let estimate = problem.get_neighbor_delta(&param, &neighbor).unwrap();
+                assert!(param.makespan() + estimate <= new_param.makespan() + 1e-9);
+            }
+        }
+    }
+}
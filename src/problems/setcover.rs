@@ -0,0 +1,413 @@
+//! Set covering: select columns of minimum total cost so that every row is covered by at
+//! least one selected column. Uncovered rows are penalized in the cost, so that the
+//! search can go through infeasible selections.
+use super::{parse_token, read_instance};
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetCover {
+    costs: Vec<f64>,
+    /// Sorted rows covered by each column.
+    columns: Vec<Vec<usize>>,
+    /// Columns covering each row.
+    rows: Vec<Vec<usize>>,
+    penalty: f64,
+}
+
+/// Selected columns with the number of selected columns covering each row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cover {
+    selected: Vec<bool>,
+    coverage: Vec<u32>,
+    cost: f64,
+    uncovered: usize,
+}
+
+impl Cover {
+    pub fn selected(&self) -> &[bool] {
+        &self.selected
+    }
+
+    /// Total cost of the selected columns, without penalty.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn num_uncovered(&self) -> usize {
+        self.uncovered
+    }
+
+    pub fn is_feasible(&self) -> bool {
+        self.uncovered == 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SetCoverMove {
+    Add(usize),
+    Drop(usize),
+    Swap { drop: usize, add: usize },
+}
+
+impl SetCover {
+    /// `columns[j]` lists the rows covered by column `j`. The penalty per uncovered row
+    /// defaults to twice the largest column cost, so that covering a row always pays.
+    ///
+    /// # Panics
+    ///
+    /// Panic if `costs` and `columns` have different lengths or a row is out of range.
+    pub fn new(num_rows: usize, costs: Vec<f64>, mut columns: Vec<Vec<usize>>) -> Self {
+        assert_eq!(costs.len(), columns.len());
+        let mut rows = vec![Vec::new(); num_rows];
+        for (j, column) in columns.iter_mut().enumerate() {
+            column.sort_unstable();
+            column.dedup();
+            for &row in column.iter() {
+                rows[row].push(j);
+            }
+        }
+        let penalty = 2.0 * costs.iter().copied().fold(0.5, f64::max);
+        Self {
+            costs,
+            columns,
+            rows,
+            penalty,
+        }
+    }
+
+    #[must_use]
+    pub fn with_penalty(mut self, penalty: f64) -> Self {
+        self.penalty = penalty;
+        self
+    }
+
+    /// Parse an instance in the OR-Library `scp` format: the number of rows and columns,
+    /// the cost of every column, then for every row the number of columns covering it
+    /// followed by these columns, numbered from 1.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the input is truncated or a column is out of range.
+    pub fn from_orlib(input: &str) -> Result<Self, LocalSearchError> {
+        let mut tokens = input.split_whitespace();
+        let num_rows: usize = parse_token(tokens.next(), "number of rows")?;
+        let num_columns: usize = parse_token(tokens.next(), "number of columns")?;
+        let costs = (0..num_columns)
+            .map(|_| parse_token(tokens.next(), "column cost"))
+            .collect::<Result<Vec<f64>, _>>()?;
+        let mut columns = vec![Vec::new(); num_columns];
+        for row in 0..num_rows {
+            let count: usize = parse_token(tokens.next(), "row size")?;
+            for _ in 0..count {
+                let column: usize = parse_token(tokens.next(), "column")?;
+                if column == 0 || column > num_columns {
+                    return Err(LocalSearchError::FailReadInstance(format!(
+                        "column {column} out of range"
+                    )));
+                }
+                columns[column - 1].push(row);
+            }
+        }
+        Ok(Self::new(num_rows, costs, columns))
+    }
+
+    /// Read a file in the OR-Library `scp` format.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed.
+    pub fn read_orlib<P: AsRef<Path>>(path: P) -> Result<Self, LocalSearchError> {
+        Self::from_orlib(&read_instance(path)?)
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Compute the coverage of a selection.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the selection does not have one entry per column.
+    pub fn cover(&self, selected: Vec<bool>) -> Result<Cover, LocalSearchError> {
+        if selected.len() != self.num_columns() {
+            return Err(LocalSearchError::InvalidParam(format!(
+                "{} selections for {} columns",
+                selected.len(),
+                self.num_columns()
+            )));
+        }
+        let mut coverage = vec![0; self.num_rows()];
+        let mut cost = 0.0;
+        for (j, _) in selected.iter().enumerate().filter(|(_, s)| **s) {
+            cost += self.costs[j];
+            for &row in &self.columns[j] {
+                coverage[row] += 1;
+            }
+        }
+        Ok(Cover {
+            uncovered: coverage.iter().filter(|&&c| c == 0).count(),
+            selected,
+            coverage,
+            cost,
+        })
+    }
+
+    /// Chvátal's greedy: repeatedly select the column with the lowest cost per newly
+    /// covered row, then drop the redundant columns.
+    ///
+    /// # Errors
+    ///
+    /// Never fails, the `Result` matches the other constructors.
+    pub fn greedy(&self) -> Result<Cover, LocalSearchError> {
+        let mut param = self.cover(vec![false; self.num_columns()])?;
+        loop {
+            let best = (0..self.num_columns())
+                .filter(|&j| !param.selected[j])
+                .filter_map(|j| {
+                    let covered = self.columns[j]
+                        .iter()
+                        .filter(|&&i| param.coverage[i] == 0)
+                        .count();
+                    (covered > 0).then(|| (j, self.costs[j] / covered as f64))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match best {
+                Some((j, _)) => param = self.make_move(&param, &SetCoverMove::Add(j))?,
+                None => break,
+            }
+        }
+        let mut selected: Vec<usize> = (0..self.num_columns())
+            .filter(|&j| param.selected[j])
+            .collect();
+        selected.sort_by(|&a, &b| self.costs[b].total_cmp(&self.costs[a]));
+        for j in selected {
+            if self.columns[j].iter().all(|&i| param.coverage[i] > 1) {
+                param = self.make_move(&param, &SetCoverMove::Drop(j))?;
+            }
+        }
+        Ok(param)
+    }
+
+    /// Cover the uncovered rows in random order with random columns, then drop the
+    /// redundant columns in random order.
+    ///
+    /// # Errors
+    ///
+    /// Never fails, the `Result` matches the other constructors.
+    pub fn init_solution<R: Rng>(&self, rng: &mut R) -> Result<Cover, LocalSearchError> {
+        let mut param = self.cover(vec![false; self.num_columns()])?;
+        let mut rows: Vec<usize> = (0..self.num_rows()).collect();
+        rows.shuffle(rng);
+        for i in rows {
+            if param.coverage[i] == 0 {
+                if let Some(&j) = self.rows[i].choose(rng) {
+                    param = self.make_move(&param, &SetCoverMove::Add(j))?;
+                }
+            }
+        }
+        let mut selected: Vec<usize> = (0..self.num_columns())
+            .filter(|&j| param.selected[j])
+            .collect();
+        selected.shuffle(rng);
+        for j in selected {
+            if self.columns[j].iter().all(|&i| param.coverage[i] > 1) {
+                param = self.make_move(&param, &SetCoverMove::Drop(j))?;
+            }
+        }
+        Ok(param)
+    }
+
+    /// Change of the number of uncovered rows.
+    fn uncovered_delta(&self, param: &Cover, mv: &SetCoverMove) -> isize {
+        let newly_covered = |add: usize| {
+            self.columns[add]
+                .iter()
+                .filter(|&&i| param.coverage[i] == 0)
+                .count() as isize
+        };
+        let newly_uncovered = |drop: usize, add: Option<usize>| {
+            self.columns[drop]
+                .iter()
+                .filter(|&&i| {
+                    param.coverage[i] == 1
+                        && add.is_none_or(|add| self.columns[add].binary_search(&i).is_err())
+                })
+                .count() as isize
+        };
+        match *mv {
+            SetCoverMove::Add(j) => -newly_covered(j),
+            SetCoverMove::Drop(j) => newly_uncovered(j, None),
+            SetCoverMove::Swap { drop, add } => {
+                newly_uncovered(drop, Some(add)) - newly_covered(add)
+            }
+        }
+    }
+
+    fn cost_delta(&self, mv: &SetCoverMove) -> f64 {
+        match *mv {
+            SetCoverMove::Add(j) => self.costs[j],
+            SetCoverMove::Drop(j) => -self.costs[j],
+            SetCoverMove::Swap { drop, add } => self.costs[add] - self.costs[drop],
+        }
+    }
+}
+
+impl CostFunction for SetCover {
+    type Param = Cover;
+
+    fn cost(&self, param: &Cover) -> Result<f64, LocalSearchError> {
+        let full = self.cover(param.selected.clone())?;
+        Ok(full.cost + self.penalty * full.uncovered as f64)
+    }
+}
+
+impl Neighborhood for SetCover {
+    type Param = Cover;
+    type Neighbor = SetCoverMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<Cover, LocalSearchError> {
+        self.init_solution(rng)
+    }
+
+    /// Adds of columns covering an uncovered row, drops of selected columns, and swaps of
+    /// a selected column with a column covering one of the rows only it covers.
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Cover,
+    ) -> Result<Vec<SetCoverMove>, LocalSearchError> {
+        let mut moves: Vec<SetCoverMove> = (0..self.num_rows())
+            .filter(|&i| param.coverage[i] == 0)
+            .flat_map(|i| self.rows[i].iter().map(|&j| SetCoverMove::Add(j)))
+            .collect();
+        moves.sort_unstable_by_key(|mv| match mv {
+            SetCoverMove::Add(j) => *j,
+            _ => unreachable!(),
+        });
+        moves.dedup();
+        for drop in (0..self.num_columns()).filter(|&j| param.selected[j]) {
+            moves.push(SetCoverMove::Drop(drop));
+            let mut candidates: Vec<usize> = self.columns[drop]
+                .iter()
+                .filter(|&&i| param.coverage[i] == 1)
+                .flat_map(|&i| self.rows[i].iter().copied())
+                .filter(|&add| !param.selected[add])
+                .collect();
+            candidates.sort_unstable();
+            candidates.dedup();
+            moves.extend(
+                candidates
+                    .into_iter()
+                    .map(|add| SetCoverMove::Swap { drop, add }),
+            );
+        }
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Cover,
+        neighbor: &SetCoverMove,
+    ) -> Result<f64, LocalSearchError> {
+        Ok(self.cost_delta(neighbor) + self.penalty * self.uncovered_delta(param, neighbor) as f64)
+    }
+
+    fn make_move(&self, param: &Cover, neighbor: &SetCoverMove) -> Result<Cover, LocalSearchError> {
+        let uncovered = param.uncovered as isize + self.uncovered_delta(param, neighbor);
+        let mut new_param = param.clone();
+        let (added, dropped) = match *neighbor {
+            SetCoverMove::Add(j) => (Some(j), None),
+            SetCoverMove::Drop(j) => (None, Some(j)),
+            SetCoverMove::Swap { drop, add } => (Some(add), Some(drop)),
+        };
+        if let Some(j) = dropped {
+            new_param.selected[j] = false;
+            for &i in &self.columns[j] {
+                new_param.coverage[i] -= 1;
+            }
+        }
+        if let Some(j) = added {
+            new_param.selected[j] = true;
+            for &i in &self.columns[j] {
+                new_param.coverage[i] += 1;
+            }
+        }
+        new_param.cost += self.cost_delta(neighbor);
+        new_param.uncovered = uncovered as usize;
+        Ok(new_param)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cover, SetCover};
+    use crate::errors::LocalSearchError;
+    use crate::problem::{CostFunction, Neighborhood};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SCP: &str = " 4 5
+ 1 2 1 3 1
+ 2 1 2
+ 2 2 3
+ 3 1 3 4
+ 2 4 5
+";
+
+    #[test]
+    fn test_from_orlib() {
+        let problem = SetCover::from_orlib(SCP).unwrap();
+        assert_eq!(problem.num_rows(), 4);
+        assert_eq!(problem.num_columns(), 5);
+        assert_eq!(problem.columns[0], vec![0, 2]);
+        assert!(SetCover::from_orlib(" 1 1\n 1\n 1 2\n").is_err());
+        assert!(matches!(
+            problem.cover(vec![true; 4]),
+            Err(LocalSearchError::InvalidParam(_))
+        ));
+    }
+
+    #[test]
+    fn test_delta() {
+        let problem = SetCover::from_orlib(SCP).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let param = problem.greedy().unwrap();
+        assert!(param.is_feasible());
+        let start = problem
+            .cover(vec![false, true, false, false, false])
+            .unwrap();
+        for param in [param, start] {
+            for neighbor in problem.get_neighbor_moves(&mut rng, &param).unwrap() {
+                let delta = problem.get_neighbor_delta(&param, &neighbor).unwrap();
+                let new_param = problem.make_move(&param, &neighbor).unwrap();
+                let expected = problem.cost(&new_param).unwrap() - problem.cost(&param).unwrap();
+                assert!((expected - delta).abs() < 1e-9);
+                assert_eq!(
+                    new_param,
+                    problem.cover(new_param.selected().to_vec()).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_param() {
+        let problem = SetCover::from_orlib(SCP).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let params: Vec<Cover> = (0..10)
+            .map(|_| problem.random_param(&mut rng).unwrap())
+            .collect();
+        assert!(params.iter().all(Cover::is_feasible));
+        assert!(params.iter().any(|param| param != &params[0]));
+    }
+}
@@ -0,0 +1,97 @@
+//! Path relinking, an intensification phase usable after any solver: walk from one elite
+//! param to another with the problem's own moves, greedily picking the best move toward
+//! the guide at each step, and keep the best param met on the way.
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood, Relinking};
+use crate::state::ElitePool;
+
+/// Best param strictly between `from` and `to` on the greedy path, with its cost. `None`
+/// when the path has no intermediate param.
+///
+/// # Errors
+///
+/// Will return `Err` if a move toward `to` cannot be generated, evaluated or applied.
+pub fn relink<O, P, N>(problem: &O, from: &P, to: &P) -> Result<Option<(P, f64)>, LocalSearchError>
+where
+    O: CostFunction<Param = P>
+        + Neighborhood<Param = P, Neighbor = N>
+        + Relinking<Param = P, Neighbor = N>,
+    P: Clone,
+{
+    let mut param = from.clone();
+    let mut cost = problem.cost(&param)?;
+    let mut best: Option<(P, f64)> = None;
+    let mut intermediate = false;
+    loop {
+        let moves = problem.moves_toward(&param, to)?;
+        if moves.is_empty() {
+            break;
+        }
+        if intermediate && best.as_ref().is_none_or(|(_, best_cost)| cost < *best_cost) {
+            best = Some((param.clone(), cost));
+        }
+        let mut selected: Option<(N, f64)> = None;
+        for neighbor in moves {
+            let delta = problem.get_neighbor_delta(&param, &neighbor)?;
+            if selected
+                .as_ref()
+                .is_none_or(|(_, best_delta)| delta < *best_delta)
+            {
+                selected = Some((neighbor, delta));
+            }
+        }
+        let (neighbor, delta) = selected.unwrap();
+        problem.apply_move(&mut param, &neighbor)?;
+        cost += delta;
+        intermediate = true;
+    }
+    // The cost was accumulated from deltas
+    match best {
+        Some((param, _)) => {
+            let cost = problem.cost(&param)?;
+            Ok(Some((param, cost)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Relink every ordered pair of entries of `pool`, and insert the resulting params in it.
+/// Returns the best param found by relinking, with its cost.
+///
+/// # Errors
+///
+/// Will return `Err` if relinking a pair fails.
+pub fn relink_pool<O, P, N, D>(
+    problem: &O,
+    pool: &mut ElitePool<P>,
+    distance: D,
+) -> Result<Option<(P, f64)>, LocalSearchError>
+where
+    O: CostFunction<Param = P>
+        + Neighborhood<Param = P, Neighbor = N>
+        + Relinking<Param = P, Neighbor = N>,
+    P: Clone,
+    D: Fn(&P, &P) -> f64,
+{
+    let entries: Vec<P> = pool
+        .entries()
+        .iter()
+        .map(|(param, _)| param.clone())
+        .collect();
+    let mut best: Option<(P, f64)> = None;
+    for (i, from) in entries.iter().enumerate() {
+        for (j, to) in entries.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let Some((param, cost)) = relink(problem, from, to)? else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|(_, best_cost)| cost < *best_cost) {
+                best = Some((param.clone(), cost));
+            }
+            pool.insert(param, cost, &distance);
+        }
+    }
+    Ok(best)
+}
@@ -0,0 +1,225 @@
+use super::DeltaFn;
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood, Relinking};
+use rand::seq::{index, SliceRandom};
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BinaryMoveKind {
+    Flip,
+    /// Flip the given number of distinct random bits at once.
+    KFlip(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BinaryMove {
+    Flip(usize),
+    KFlip(Vec<usize>),
+}
+
+impl BinaryMove {
+    pub fn apply(&self, param: &mut [bool]) {
+        match self {
+            Self::Flip(i) => param[*i] = !param[*i],
+            Self::KFlip(bits) => {
+                for &i in bits {
+                    param[i] = !param[i];
+                }
+            }
+        }
+    }
+}
+
+/// Problems encoded as a bit string.
+pub struct BinaryProblem<C> {
+    cost_fn: C,
+    kinds: Vec<BinaryMoveKind>,
+    sample_size: Option<usize>,
+    delta: Option<DeltaFn<C, Vec<bool>, BinaryMove>>,
+    len: Option<usize>,
+}
+
+impl<C> BinaryProblem<C>
+where
+    C: CostFunction<Param = Vec<bool>>,
+{
+    /// By default the neighborhood is made of all single bit flips.
+    pub fn new(cost_fn: C) -> Self {
+        Self {
+            cost_fn,
+            kinds: vec![BinaryMoveKind::Flip],
+            sample_size: None,
+            delta: None,
+            len: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_moves(mut self, kinds: &[BinaryMoveKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    /// Draw `sample_size` random moves instead of enumerating the single flips.
+    /// k-flip moves are always drawn at random, `n` of them when not sampling.
+    #[must_use]
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.sample_size = Some(sample_size);
+        self
+    }
+
+    #[must_use]
+    pub fn with_delta<D>(mut self, delta: D) -> Self
+    where
+        D: Fn(&C, &Vec<bool>, &BinaryMove) -> Result<f64, LocalSearchError> + Send + Sync + 'static,
+    {
+        self.delta = Some(Box::new(delta));
+        self
+    }
+
+    /// Length of the bit strings drawn by `Neighborhood::random_param`, which fails without
+    /// it.
+    #[must_use]
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    pub fn cost_fn(&self) -> &C {
+        &self.cost_fn
+    }
+
+    /// Draw `len` independent fair bits.
+    pub fn init_solution<R: Rng>(len: usize, rng: &mut R) -> Vec<bool> {
+        (0..len).map(|_| rng.gen()).collect()
+    }
+
+    fn random_move<R: Rng>(kind: BinaryMoveKind, n: usize, rng: &mut R) -> BinaryMove {
+        match kind {
+            BinaryMoveKind::Flip => BinaryMove::Flip(rng.gen_range(0..n)),
+            BinaryMoveKind::KFlip(k) => {
+                BinaryMove::KFlip(index::sample(rng, n, k.min(n)).into_vec())
+            }
+        }
+    }
+}
+
+impl<C> CostFunction for BinaryProblem<C>
+where
+    C: CostFunction<Param = Vec<bool>>,
+{
+    type Param = Vec<bool>;
+
+    fn cost(&self, param: &Vec<bool>) -> Result<f64, LocalSearchError> {
+        self.cost_fn.cost(param)
+    }
+}
+
+impl<C> Neighborhood for BinaryProblem<C>
+where
+    C: CostFunction<Param = Vec<bool>>,
+{
+    type Param = Vec<bool>;
+    type Neighbor = BinaryMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<Vec<bool>, LocalSearchError> {
+        let len = self.len.ok_or(LocalSearchError::FailGenRandomState)?;
+        Ok(Self::init_solution(len, rng))
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Vec<bool>,
+    ) -> Result<Vec<BinaryMove>, LocalSearchError> {
+        let n = param.len();
+        if n == 0 || self.kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some(size) = self.sample_size {
+            return Ok((0..size)
+                .map(|_| {
+                    let kind = *self.kinds.choose(rng).unwrap();
+                    Self::random_move(kind, n, rng)
+                })
+                .collect());
+        }
+        let mut moves = Vec::new();
+        for kind in &self.kinds {
+            match *kind {
+                BinaryMoveKind::Flip => moves.extend((0..n).map(BinaryMove::Flip)),
+                BinaryMoveKind::KFlip(_) => {
+                    moves.extend((0..n).map(|_| Self::random_move(*kind, n, rng)));
+                }
+            }
+        }
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Vec<bool>,
+        neighbor: &BinaryMove,
+    ) -> Result<f64, LocalSearchError> {
+        match &self.delta {
+            Some(delta) => delta(&self.cost_fn, param, neighbor),
+            None => {
+                Ok(self.cost_fn.cost(&self.make_move(param, neighbor)?)?
+                    - self.cost_fn.cost(param)?)
+            }
+        }
+    }
+
+    fn make_move(
+        &self,
+        param: &Vec<bool>,
+        neighbor: &BinaryMove,
+    ) -> Result<Vec<bool>, LocalSearchError> {
+        let mut new_param = param.clone();
+        neighbor.apply(&mut new_param);
+        Ok(new_param)
+    }
+
+    fn apply_move(
+        &self,
+        param: &mut Vec<bool>,
+        neighbor: &BinaryMove,
+    ) -> Result<(), LocalSearchError> {
+        neighbor.apply(param);
+        Ok(())
+    }
+
+    fn undo_move(
+        &self,
+        param: &mut Vec<bool>,
+        neighbor: &BinaryMove,
+    ) -> Result<(), LocalSearchError> {
+        // Flips are their own inverse
+        neighbor.apply(param);
+        Ok(())
+    }
+}
+
+/// Flips of the bits differing from the guide.
+impl<C> Relinking for BinaryProblem<C>
+where
+    C: CostFunction<Param = Vec<bool>>,
+{
+    type Param = Vec<bool>;
+    type Neighbor = BinaryMove;
+
+    fn moves_toward(
+        &self,
+        param: &Vec<bool>,
+        guide: &Vec<bool>,
+    ) -> Result<Vec<BinaryMove>, LocalSearchError> {
+        Ok(param
+            .iter()
+            .zip(guide)
+            .enumerate()
+            .filter(|(_, (bit, target))| bit != target)
+            .map(|(i, _)| BinaryMove::Flip(i))
+            .collect())
+    }
+}
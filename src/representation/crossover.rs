@@ -0,0 +1,219 @@
+//! Recombination of two parents into an offspring, used by `crate::solver::Memetic`. The
+//! permutation crossovers expect permutations of `0..n`, as built by `PermutationProblem`.
+use crate::errors::LocalSearchError;
+use rand::Rng;
+
+pub trait Crossover<P> {
+    /// # Errors
+    ///
+    /// Will return `Err` if the parents cannot be recombined, e.g. they differ in length.
+    fn crossover<R: Rng>(&self, first: &P, second: &P, rng: &mut R) -> Result<P, LocalSearchError>;
+}
+
+/// Order crossover (OX): keep a random segment of the first parent, and fill the other
+/// positions with the missing elements in the order of the second parent, starting after
+/// the segment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OrderCrossover;
+
+/// Partially mapped crossover (PMX): keep a random segment of the first parent, and the
+/// other positions of the second parent, mapping the elements already in the segment
+/// through the segment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PartiallyMappedCrossover;
+
+/// Cycle crossover (CX): take the positions of alternate cycles from alternate parents,
+/// so that every element keeps its position in one of the parents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CycleCrossover;
+
+/// Take each element from either parent with equal probability, for vectors of any type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UniformCrossover;
+
+fn check_lengths<T>(first: &[T], second: &[T]) -> Result<(), LocalSearchError> {
+    if first.len() == second.len() {
+        Ok(())
+    } else {
+        Err(LocalSearchError::FailCrossover(format!(
+            "parents of lengths {} and {}",
+            first.len(),
+            second.len()
+        )))
+    }
+}
+
+/// Bounds of a random segment `lo..=hi`.
+fn random_segment<R: Rng>(n: usize, rng: &mut R) -> (usize, usize) {
+    let (i, j) = (rng.gen_range(0..n), rng.gen_range(0..n));
+    (i.min(j), i.max(j))
+}
+
+/// Position of each element in `param`.
+fn positions(param: &[usize]) -> Vec<usize> {
+    let mut positions = vec![0; param.len()];
+    for (i, &value) in param.iter().enumerate() {
+        positions[value] = i;
+    }
+    positions
+}
+
+impl Crossover<Vec<usize>> for OrderCrossover {
+    fn crossover<R: Rng>(
+        &self,
+        first: &Vec<usize>,
+        second: &Vec<usize>,
+        rng: &mut R,
+    ) -> Result<Vec<usize>, LocalSearchError> {
+        check_lengths(first, second)?;
+        let n = first.len();
+        if n < 2 {
+            return Ok(first.clone());
+        }
+        let (lo, hi) = random_segment(n, rng);
+        let mut child = first.clone();
+        let mut used = vec![false; n];
+        for &value in &first[lo..=hi] {
+            used[value] = true;
+        }
+        let mut fill = (hi + 1..n)
+            .chain(0..=hi)
+            .map(|i| second[i])
+            .filter(|&value| !used[value]);
+        for i in (hi + 1..n).chain(0..lo) {
+            child[i] = fill.next().unwrap();
+        }
+        Ok(child)
+    }
+}
+
+impl Crossover<Vec<usize>> for PartiallyMappedCrossover {
+    fn crossover<R: Rng>(
+        &self,
+        first: &Vec<usize>,
+        second: &Vec<usize>,
+        rng: &mut R,
+    ) -> Result<Vec<usize>, LocalSearchError> {
+        check_lengths(first, second)?;
+        let n = first.len();
+        if n < 2 {
+            return Ok(first.clone());
+        }
+        let (lo, hi) = random_segment(n, rng);
+        let first_positions = positions(first);
+        let mut child = second.clone();
+        child[lo..=hi].copy_from_slice(&first[lo..=hi]);
+        for i in (0..lo).chain(hi + 1..n) {
+            let mut value = second[i];
+            while (lo..=hi).contains(&first_positions[value]) {
+                value = second[first_positions[value]];
+            }
+            child[i] = value;
+        }
+        Ok(child)
+    }
+}
+
+impl Crossover<Vec<usize>> for CycleCrossover {
+    fn crossover<R: Rng>(
+        &self,
+        first: &Vec<usize>,
+        second: &Vec<usize>,
+        _rng: &mut R,
+    ) -> Result<Vec<usize>, LocalSearchError> {
+        check_lengths(first, second)?;
+        let first_positions = positions(first);
+        let mut child = first.clone();
+        let mut visited = vec![false; first.len()];
+        let mut from_second = false;
+        for start in 0..first.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut i = start;
+            while !visited[i] {
+                visited[i] = true;
+                if from_second {
+                    child[i] = second[i];
+                }
+                i = first_positions[second[i]];
+            }
+            from_second = !from_second;
+        }
+        Ok(child)
+    }
+}
+
+impl<T: Clone> Crossover<Vec<T>> for UniformCrossover {
+    fn crossover<R: Rng>(
+        &self,
+        first: &Vec<T>,
+        second: &Vec<T>,
+        rng: &mut R,
+    ) -> Result<Vec<T>, LocalSearchError> {
+        check_lengths(first, second)?;
+        Ok(first
+            .iter()
+            .zip(second)
+            .map(|(a, b)| if rng.gen() { a.clone() } else { b.clone() })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Crossover, CycleCrossover, OrderCrossover, PartiallyMappedCrossover, UniformCrossover,
+    };
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    fn is_permutation(param: &[usize]) -> bool {
+        let mut sorted = param.to_vec();
+        sorted.sort_unstable();
+        sorted.iter().enumerate().all(|(i, &v)| i == v)
+    }
+
+    #[test]
+    fn test_permutation_crossovers() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..50 {
+            let mut first: Vec<usize> = (0..9).collect();
+            let mut second = first.clone();
+            first.shuffle(&mut rng);
+            second.shuffle(&mut rng);
+            let ox = OrderCrossover.crossover(&first, &second, &mut rng).unwrap();
+            let pmx = PartiallyMappedCrossover
+                .crossover(&first, &second, &mut rng)
+                .unwrap();
+            let cx = CycleCrossover.crossover(&first, &second, &mut rng).unwrap();
+            assert!(is_permutation(&ox), "{ox:?}");
+            assert!(is_permutation(&pmx), "{pmx:?}");
+            assert!(is_permutation(&cx), "{cx:?}");
+            assert!((0..9).all(|i| cx[i] == first[i] || cx[i] == second[i]));
+        }
+    }
+
+    #[test]
+    fn test_cycle_crossover() {
+        let first = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let second = vec![1, 0, 3, 2, 5, 4, 7, 6];
+        let child = CycleCrossover
+            .crossover(&first, &second, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(child, vec![0, 1, 3, 2, 4, 5, 7, 6]);
+    }
+
+    #[test]
+    fn test_uniform_crossover() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let child = UniformCrossover
+            .crossover(&vec![true; 20], &vec![false; 20], &mut rng)
+            .unwrap();
+        assert!(child.contains(&true) && child.contains(&false));
+        assert!(UniformCrossover
+            .crossover(&vec![1, 2], &vec![1], &mut rng)
+            .is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use super::DeltaFn;
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IntegerMoveKind {
+    /// Increment and decrement moves.
+    Step,
+    /// Assign a random value of the domain.
+    Reassign,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IntegerMove {
+    Increment(usize),
+    Decrement(usize),
+    /// `old` is the value replaced, so that the move can be undone.
+    Reassign {
+        index: usize,
+        value: i64,
+        old: i64,
+    },
+}
+
+impl IntegerMove {
+    pub fn apply(&self, param: &mut [i64]) {
+        match *self {
+            Self::Increment(i) => param[i] += 1,
+            Self::Decrement(i) => param[i] -= 1,
+            Self::Reassign { index, value, .. } => param[index] = value,
+        }
+    }
+}
+
+/// Problems encoded as a vector of integers, each within inclusive bounds.
+pub struct IntegerProblem<C> {
+    cost_fn: C,
+    bounds: Vec<(i64, i64)>,
+    kinds: Vec<IntegerMoveKind>,
+    sample_size: Option<usize>,
+    delta: Option<DeltaFn<C, Vec<i64>, IntegerMove>>,
+}
+
+impl<C> IntegerProblem<C>
+where
+    C: CostFunction<Param = Vec<i64>>,
+{
+    /// By default the neighborhood is made of all increment and decrement moves staying
+    /// within bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panic if a lower bound is greater than its upper bound.
+    pub fn new(cost_fn: C, bounds: Vec<(i64, i64)>) -> Self {
+        assert!(bounds.iter().all(|(lower, upper)| lower <= upper));
+        Self {
+            cost_fn,
+            bounds,
+            kinds: vec![IntegerMoveKind::Step],
+            sample_size: None,
+            delta: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_moves(mut self, kinds: &[IntegerMoveKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    /// Draw `sample_size` random moves instead of enumerating the step moves.
+    /// Reassign moves are always drawn at random, one per variable when not sampling.
+    #[must_use]
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.sample_size = Some(sample_size);
+        self
+    }
+
+    #[must_use]
+    pub fn with_delta<D>(mut self, delta: D) -> Self
+    where
+        D: Fn(&C, &Vec<i64>, &IntegerMove) -> Result<f64, LocalSearchError> + Send + Sync + 'static,
+    {
+        self.delta = Some(Box::new(delta));
+        self
+    }
+
+    pub fn cost_fn(&self) -> &C {
+        &self.cost_fn
+    }
+
+    pub fn bounds(&self) -> &[(i64, i64)] {
+        &self.bounds
+    }
+
+    /// Draw every variable uniformly within its bounds.
+    pub fn init_solution<R: Rng>(&self, rng: &mut R) -> Vec<i64> {
+        self.bounds
+            .iter()
+            .map(|&(lower, upper)| rng.gen_range(lower..=upper))
+            .collect()
+    }
+
+    fn step_moves(&self, param: &[i64], index: usize) -> impl Iterator<Item = IntegerMove> {
+        let (lower, upper) = self.bounds[index];
+        let value = param[index];
+        let inc = (value < upper).then_some(IntegerMove::Increment(index));
+        let dec = (value > lower).then_some(IntegerMove::Decrement(index));
+        inc.into_iter().chain(dec)
+    }
+
+    fn reassign<R: Rng>(&self, param: &[i64], index: usize, rng: &mut R) -> Option<IntegerMove> {
+        let (lower, upper) = self.bounds[index];
+        if lower == upper {
+            return None;
+        }
+        // Draw among the values other than the current one
+        let old = param[index];
+        let mut value = rng.gen_range(lower..upper);
+        if value >= old {
+            value += 1;
+        }
+        Some(IntegerMove::Reassign { index, value, old })
+    }
+}
+
+impl<C> CostFunction for IntegerProblem<C>
+where
+    C: CostFunction<Param = Vec<i64>>,
+{
+    type Param = Vec<i64>;
+
+    fn cost(&self, param: &Vec<i64>) -> Result<f64, LocalSearchError> {
+        self.cost_fn.cost(param)
+    }
+}
+
+impl<C> Neighborhood for IntegerProblem<C>
+where
+    C: CostFunction<Param = Vec<i64>>,
+{
+    type Param = Vec<i64>;
+    type Neighbor = IntegerMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<Vec<i64>, LocalSearchError> {
+        Ok(self.init_solution(rng))
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Vec<i64>,
+    ) -> Result<Vec<IntegerMove>, LocalSearchError> {
+        let n = param.len();
+        if n == 0 || self.kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some(size) = self.sample_size {
+            return Ok((0..size)
+                .filter_map(|_| {
+                    let index = rng.gen_range(0..n);
+                    match self.kinds.choose(rng).unwrap() {
+                        IntegerMoveKind::Step => self
+                            .step_moves(param, index)
+                            .collect::<Vec<_>>()
+                            .choose(rng)
+                            .copied(),
+                        IntegerMoveKind::Reassign => self.reassign(param, index, rng),
+                    }
+                })
+                .collect());
+        }
+        let mut moves = Vec::new();
+        for kind in &self.kinds {
+            match kind {
+                IntegerMoveKind::Step => {
+                    moves.extend((0..n).flat_map(|index| self.step_moves(param, index)));
+                }
+                IntegerMoveKind::Reassign => {
+                    moves.extend((0..n).filter_map(|index| self.reassign(param, index, rng)));
+                }
+            }
+        }
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Vec<i64>,
+        neighbor: &IntegerMove,
+    ) -> Result<f64, LocalSearchError> {
+        match &self.delta {
+            Some(delta) => delta(&self.cost_fn, param, neighbor),
+            None => {
+                Ok(self.cost_fn.cost(&self.make_move(param, neighbor)?)?
+                    - self.cost_fn.cost(param)?)
+            }
+        }
+    }
+
+    fn make_move(
+        &self,
+        param: &Vec<i64>,
+        neighbor: &IntegerMove,
+    ) -> Result<Vec<i64>, LocalSearchError> {
+        let mut new_param = param.clone();
+        neighbor.apply(&mut new_param);
+        Ok(new_param)
+    }
+
+    fn apply_move(
+        &self,
+        param: &mut Vec<i64>,
+        neighbor: &IntegerMove,
+    ) -> Result<(), LocalSearchError> {
+        neighbor.apply(param);
+        Ok(())
+    }
+
+    fn undo_move(
+        &self,
+        param: &mut Vec<i64>,
+        neighbor: &IntegerMove,
+    ) -> Result<(), LocalSearchError> {
+        match *neighbor {
+            IntegerMove::Increment(i) => param[i] -= 1,
+            IntegerMove::Decrement(i) => param[i] += 1,
+            IntegerMove::Reassign { index, old, .. } => param[index] = old,
+        }
+        Ok(())
+    }
+}
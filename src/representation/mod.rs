@@ -0,0 +1,21 @@
+//! Reusable solution encodings with their standard moves. The user only supplies a
+//! `CostFunction` over the encoding, the wrapper provides the `Neighborhood`.
+//!
+//! Deltas fall back to a full re-evaluation of the neighbor unless a delta function is
+//! registered with `with_delta`.
+pub mod binary;
+pub mod crossover;
+pub mod integer;
+pub mod permutation;
+
+pub use binary::{BinaryMove, BinaryMoveKind, BinaryProblem};
+pub use crossover::{
+    Crossover, CycleCrossover, OrderCrossover, PartiallyMappedCrossover, UniformCrossover,
+};
+pub use integer::{IntegerMove, IntegerMoveKind, IntegerProblem};
+pub use permutation::{PermutationMove, PermutationMoveKind, PermutationProblem};
+
+use crate::errors::LocalSearchError;
+
+/// Delta of applying a move to a param, given the user cost function.
+pub type DeltaFn<C, P, M> = Box<dyn Fn(&C, &P, &M) -> Result<f64, LocalSearchError> + Send + Sync>;
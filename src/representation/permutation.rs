@@ -0,0 +1,537 @@
+use super::DeltaFn;
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, NeighborStream, Neighborhood, Relinking};
+use rand::rngs::StdRng;
+use rand::seq::{index, SliceRandom};
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PermutationMoveKind {
+    Swap,
+    Insert,
+    Reverse,
+    /// Shuffle a random segment of the given length.
+    Scramble(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PermutationMove {
+    /// Exchange the elements at positions `i` and `j`.
+    Swap(usize, usize),
+    /// Remove the element at `from` and reinsert it so that it ends up at `to`.
+    Insert { from: usize, to: usize },
+    /// Reverse the segment `i..=j`, i.e. a 2-opt move.
+    Reverse(usize, usize),
+    /// Shuffle the segment `start..start + len` with a generator seeded by `seed`.
+    Scramble { start: usize, len: usize, seed: u64 },
+}
+
+impl PermutationMove {
+    pub fn apply(&self, param: &mut [usize]) {
+        match *self {
+            Self::Swap(i, j) => param.swap(i, j),
+            Self::Insert { from, to } if from < to => param[from..=to].rotate_left(1),
+            Self::Insert { from, to } => param[to..=from].rotate_right(1),
+            Self::Reverse(i, j) => param[i.min(j)..=i.max(j)].reverse(),
+            Self::Scramble { start, len, seed } => {
+                param[start..start + len].shuffle(&mut StdRng::seed_from_u64(seed));
+            }
+        }
+    }
+
+    /// Revert `apply`.
+    pub fn undo(&self, param: &mut [usize]) {
+        match *self {
+            Self::Swap(..) | Self::Reverse(..) => self.apply(param),
+            Self::Insert { from, to } => Self::Insert { from: to, to: from }.apply(param),
+            Self::Scramble { start, len, seed } => {
+                // Shuffling the positions draws the same permutation as the elements
+                let mut positions: Vec<usize> = (0..len).collect();
+                positions.shuffle(&mut StdRng::seed_from_u64(seed));
+                let segment = param[start..start + len].to_vec();
+                for (value, position) in segment.into_iter().zip(positions) {
+                    param[start + position] = value;
+                }
+            }
+        }
+    }
+}
+
+/// Problems encoded as a permutation of `0..n`.
+pub struct PermutationProblem<C> {
+    cost_fn: C,
+    kinds: Vec<PermutationMoveKind>,
+    sample_size: Option<usize>,
+    delta: Option<DeltaFn<C, Vec<usize>, PermutationMove>>,
+    len: Option<usize>,
+}
+
+impl<C> PermutationProblem<C>
+where
+    C: CostFunction<Param = Vec<usize>>,
+{
+    /// By default the neighborhood is made of all swap moves.
+    pub fn new(cost_fn: C) -> Self {
+        Self {
+            cost_fn,
+            kinds: vec![PermutationMoveKind::Swap],
+            sample_size: None,
+            delta: None,
+            len: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_moves(mut self, kinds: &[PermutationMoveKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    /// Draw `sample_size` random moves instead of enumerating the O(n²) neighborhood.
+    /// Scramble moves are always drawn at random, `n` of them when not sampling.
+    #[must_use]
+    pub fn with_sample_size(mut self, sample_size: usize) -> Self {
+        self.sample_size = Some(sample_size);
+        self
+    }
+
+    #[must_use]
+    pub fn with_delta<D>(mut self, delta: D) -> Self
+    where
+        D: Fn(&C, &Vec<usize>, &PermutationMove) -> Result<f64, LocalSearchError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.delta = Some(Box::new(delta));
+        self
+    }
+
+    /// Length of the permutations drawn by `Neighborhood::random_param`, which fails without
+    /// it.
+    #[must_use]
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    pub fn cost_fn(&self) -> &C {
+        &self.cost_fn
+    }
+
+    /// Draw a uniformly random permutation of `0..len`.
+    pub fn init_solution<R: Rng>(len: usize, rng: &mut R) -> Vec<usize> {
+        let mut param: Vec<usize> = (0..len).collect();
+        param.shuffle(rng);
+        param
+    }
+}
+
+impl<C> CostFunction for PermutationProblem<C>
+where
+    C: CostFunction<Param = Vec<usize>>,
+{
+    type Param = Vec<usize>;
+
+    fn cost(&self, param: &Vec<usize>) -> Result<f64, LocalSearchError> {
+        self.cost_fn.cost(param)
+    }
+}
+
+impl<C> Neighborhood for PermutationProblem<C>
+where
+    C: CostFunction<Param = Vec<usize>>,
+{
+    type Param = Vec<usize>;
+    type Neighbor = PermutationMove;
+
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<Vec<usize>, LocalSearchError> {
+        let len = self.len.ok_or(LocalSearchError::FailGenRandomState)?;
+        Ok(Self::init_solution(len, rng))
+    }
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Vec<usize>,
+    ) -> Result<Vec<PermutationMove>, LocalSearchError> {
+        let n = param.len();
+        if n < 2 || self.kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        if let Some(size) = self.sample_size {
+            return Ok((0..size)
+                .map(|_| {
+                    let kind = *self.kinds.choose(rng).unwrap();
+                    random_move(kind, n, rng)
+                })
+                .collect());
+        }
+        let with_swap = self.kinds.contains(&PermutationMoveKind::Swap);
+        let mut moves = Vec::new();
+        for kind in &self.kinds {
+            match *kind {
+                PermutationMoveKind::Swap => moves.extend(
+                    (0..n).flat_map(|i| (i + 1..n).map(move |j| PermutationMove::Swap(i, j))),
+                ),
+                // Inserting next to an adjacent element is the same as swapping them
+                PermutationMoveKind::Insert => moves.extend((0..n).flat_map(|from| {
+                    (0..n)
+                        .filter(move |&to| to != from && !(with_swap && to.abs_diff(from) == 1))
+                        .map(move |to| PermutationMove::Insert { from, to })
+                })),
+                // Reversing two adjacent elements is the same as swapping them
+                PermutationMoveKind::Reverse => moves.extend((0..n).flat_map(|i| {
+                    (i + 1 + usize::from(with_swap)..n).map(move |j| PermutationMove::Reverse(i, j))
+                })),
+                PermutationMoveKind::Scramble(_) => {
+                    moves.extend((0..n).map(|_| random_move(*kind, n, rng)));
+                }
+            }
+        }
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    /// Without a sample size, the moves are drawn lazily in uniformly random order, like
+    /// the shuffled `get_neighbor_moves`, without materializing the neighborhood.
+    fn neighbor_stream<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Vec<usize>,
+    ) -> Result<NeighborStream<PermutationMove>, LocalSearchError> {
+        let n = param.len();
+        if n < 2 || self.kinds.is_empty() || self.sample_size.is_some() {
+            return Ok(Box::new(self.get_neighbor_moves(rng, param)?.into_iter()));
+        }
+        let with_swap = self.kinds.contains(&PermutationMoveKind::Swap);
+        let kinds = self.kinds.clone();
+        let mut rng = StdRng::seed_from_u64(rng.gen());
+        // Every kind owns the n² indices of the pairs of positions, invalid pairs are skipped
+        let order = LazyShuffle::new(kinds.len() * n * n, StdRng::seed_from_u64(rng.gen()));
+        Ok(Box::new(order.filter_map(move |index| {
+            let (kind, pair) = (kinds[index / (n * n)], index % (n * n));
+            pair_move(kind, n, pair / n, pair % n, with_swap, &mut rng)
+        })))
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Vec<usize>,
+        neighbor: &PermutationMove,
+    ) -> Result<f64, LocalSearchError> {
+        match &self.delta {
+            Some(delta) => delta(&self.cost_fn, param, neighbor),
+            None => {
+                Ok(self.cost_fn.cost(&self.make_move(param, neighbor)?)?
+                    - self.cost_fn.cost(param)?)
+            }
+        }
+    }
+
+    fn make_move(
+        &self,
+        param: &Vec<usize>,
+        neighbor: &PermutationMove,
+    ) -> Result<Vec<usize>, LocalSearchError> {
+        let mut new_param = param.clone();
+        neighbor.apply(&mut new_param);
+        Ok(new_param)
+    }
+
+    fn apply_move(
+        &self,
+        param: &mut Vec<usize>,
+        neighbor: &PermutationMove,
+    ) -> Result<(), LocalSearchError> {
+        neighbor.apply(param);
+        Ok(())
+    }
+
+    fn undo_move(
+        &self,
+        param: &mut Vec<usize>,
+        neighbor: &PermutationMove,
+    ) -> Result<(), LocalSearchError> {
+        neighbor.undo(param);
+        Ok(())
+    }
+}
+
+/// Swaps putting an element of the guide at its position.
+impl<C> Relinking for PermutationProblem<C>
+where
+    C: CostFunction<Param = Vec<usize>>,
+{
+    type Param = Vec<usize>;
+    type Neighbor = PermutationMove;
+
+    fn moves_toward(
+        &self,
+        param: &Vec<usize>,
+        guide: &Vec<usize>,
+    ) -> Result<Vec<PermutationMove>, LocalSearchError> {
+        let mut positions = vec![0; param.len()];
+        for (i, &value) in param.iter().enumerate() {
+            positions[value] = i;
+        }
+        Ok(param
+            .iter()
+            .zip(guide)
+            .enumerate()
+            .filter(|(_, (value, target))| value != target)
+            .map(|(i, (_, &target))| {
+                let j = positions[target];
+                PermutationMove::Swap(i.min(j), i.max(j))
+            })
+            .collect())
+    }
+}
+
+fn random_move<R: Rng>(kind: PermutationMoveKind, n: usize, rng: &mut R) -> PermutationMove {
+    if let PermutationMoveKind::Scramble(len) = kind {
+        let len = len.clamp(2, n);
+        return PermutationMove::Scramble {
+            start: rng.gen_range(0..=n - len),
+            len,
+            seed: rng.gen(),
+        };
+    }
+    let pair = index::sample(rng, n, 2);
+    let (i, j) = (pair.index(0), pair.index(1));
+    match kind {
+        PermutationMoveKind::Swap => PermutationMove::Swap(i.min(j), i.max(j)),
+        PermutationMoveKind::Insert => PermutationMove::Insert { from: i, to: j },
+        _ => PermutationMove::Reverse(i.min(j), i.max(j)),
+    }
+}
+
+/// The move of a kind on the positions `i` and `j`, if it is one of the enumerated moves.
+/// Scramble moves are random, `n` of them for `i == 0`.
+fn pair_move<R: Rng>(
+    kind: PermutationMoveKind,
+    n: usize,
+    i: usize,
+    j: usize,
+    with_swap: bool,
+    rng: &mut R,
+) -> Option<PermutationMove> {
+    match kind {
+        PermutationMoveKind::Swap => (i < j).then_some(PermutationMove::Swap(i, j)),
+        // Inserting next to an adjacent element is the same as swapping them
+        PermutationMoveKind::Insert => (i != j && !(with_swap && i.abs_diff(j) == 1))
+            .then_some(PermutationMove::Insert { from: i, to: j }),
+        // Reversing two adjacent elements is the same as swapping them
+        PermutationMoveKind::Reverse => {
+            (i + usize::from(with_swap) < j).then_some(PermutationMove::Reverse(i, j))
+        }
+        PermutationMoveKind::Scramble(_) => (i == 0).then(|| random_move(kind, n, rng)),
+    }
+}
+
+/// Fisher-Yates shuffle of `0..len` drawn lazily: each index costs O(1), and the memory
+/// grows with the number of indices drawn only.
+struct LazyShuffle {
+    len: usize,
+    drawn: usize,
+    swapped: HashMap<usize, usize>,
+    rng: StdRng,
+}
+
+impl LazyShuffle {
+    fn new(len: usize, rng: StdRng) -> Self {
+        Self {
+            len,
+            drawn: 0,
+            swapped: HashMap::new(),
+            rng,
+        }
+    }
+}
+
+impl Iterator for LazyShuffle {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.drawn == self.len {
+            return None;
+        }
+        let j = self.rng.gen_range(self.drawn..self.len);
+        let value = self.swapped.get(&j).copied().unwrap_or(j);
+        let first = self.swapped.remove(&self.drawn).unwrap_or(self.drawn);
+        if j != self.drawn {
+            self.swapped.insert(j, first);
+        }
+        self.drawn += 1;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LazyShuffle, PermutationMove, PermutationMoveKind, PermutationProblem};
+    use crate::errors::LocalSearchError;
+    use crate::problem::{CostFunction, Neighborhood, Relinking};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    struct Identity;
+
+    impl CostFunction for Identity {
+        type Param = Vec<usize>;
+
+        fn cost(&self, param: &Vec<usize>) -> Result<f64, LocalSearchError> {
+            Ok(param.iter().enumerate().filter(|(i, v)| i != *v).count() as f64)
+        }
+    }
+
+    #[test]
+    fn test_neighbor_stream() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let problem = PermutationProblem::new(Identity).with_moves(&[
+            PermutationMoveKind::Swap,
+            PermutationMoveKind::Insert,
+            PermutationMoveKind::Reverse,
+        ]);
+        let param: Vec<usize> = (0..7).collect();
+        let moves = problem.get_neighbor_moves(&mut rng, &param).unwrap();
+        let stream: Vec<_> = problem.neighbor_stream(&mut rng, &param).unwrap().collect();
+        assert_eq!(moves.len(), stream.len());
+        assert_eq!(
+            moves.into_iter().collect::<HashSet<_>>(),
+            stream.into_iter().collect::<HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn test_distinct_neighbors() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let problem = PermutationProblem::new(Identity)
+            .with_moves(&[PermutationMoveKind::Swap, PermutationMoveKind::Insert]);
+        let param: Vec<usize> = (0..7).collect();
+        let moves = problem.get_neighbor_moves(&mut rng, &param).unwrap();
+        let neighbors: HashSet<Vec<usize>> = moves
+            .iter()
+            .map(|neighbor| problem.make_move(&param, neighbor).unwrap())
+            .collect();
+        assert_eq!(neighbors.len(), moves.len());
+    }
+
+    #[test]
+    fn test_random_param() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let problem = PermutationProblem::new(Identity);
+        assert!(problem.random_param(&mut rng).is_err());
+        let mut param = problem.with_len(10).random_param(&mut rng).unwrap();
+        param.sort_unstable();
+        assert_eq!(param, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_lazy_shuffle() {
+        let order: Vec<usize> = LazyShuffle::new(50, StdRng::seed_from_u64(0)).collect();
+        assert_ne!(order, (0..50).collect::<Vec<_>>());
+        let mut sorted = order;
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_stream_sample() {
+        // The first moves drawn are spread over the positions
+        let mut rng = StdRng::seed_from_u64(1);
+        let problem = PermutationProblem::new(Identity);
+        let param: Vec<usize> = (0..100).collect();
+        let firsts: HashSet<usize> = problem
+            .neighbor_stream(&mut rng, &param)
+            .unwrap()
+            .take(20)
+            .map(|mv| match mv {
+                PermutationMove::Swap(i, _) => i,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert!(firsts.len() > 10);
+    }
+
+    #[test]
+    fn test_adjacent_reverse() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let param: Vec<usize> = (0..4).collect();
+        let reverse = PermutationProblem::new(Identity).with_moves(&[PermutationMoveKind::Reverse]);
+        let moves = reverse.get_neighbor_moves(&mut rng, &param).unwrap();
+        assert_eq!(moves.len(), 6);
+        assert!(moves.contains(&PermutationMove::Reverse(0, 1)));
+        let stream: Vec<_> = reverse.neighbor_stream(&mut rng, &param).unwrap().collect();
+        assert_eq!(stream.len(), 6);
+
+        let both = reverse.with_moves(&[PermutationMoveKind::Swap, PermutationMoveKind::Reverse]);
+        let moves = both.get_neighbor_moves(&mut rng, &param).unwrap();
+        assert_eq!(moves.len(), 6 + 3);
+        assert!(!moves.contains(&PermutationMove::Reverse(0, 1)));
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut param = vec![0, 1, 2, 3, 4];
+        PermutationMove::Swap(0, 4).apply(&mut param);
+        assert_eq!(param, vec![4, 1, 2, 3, 0]);
+        PermutationMove::Insert { from: 0, to: 3 }.apply(&mut param);
+        assert_eq!(param, vec![1, 2, 3, 4, 0]);
+        PermutationMove::Insert { from: 4, to: 1 }.apply(&mut param);
+        assert_eq!(param, vec![1, 0, 2, 3, 4]);
+        PermutationMove::Reverse(1, 3).apply(&mut param);
+        assert_eq!(param, vec![1, 3, 2, 0, 4]);
+        PermutationMove::Scramble {
+            start: 1,
+            len: 3,
+            seed: 7,
+        }
+        .apply(&mut param);
+        assert_eq!(param[0], 1);
+        assert_eq!(param[4], 4);
+        param.sort_unstable();
+        assert_eq!(param, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_undo() {
+        let moves = [
+            PermutationMove::Swap(0, 4),
+            PermutationMove::Insert { from: 0, to: 3 },
+            PermutationMove::Insert { from: 4, to: 1 },
+            PermutationMove::Reverse(1, 3),
+            PermutationMove::Scramble {
+                start: 1,
+                len: 4,
+                seed: 7,
+            },
+        ];
+        for mv in moves {
+            let mut param = vec![0, 1, 2, 3, 4, 5];
+            mv.apply(&mut param);
+            mv.undo(&mut param);
+            assert_eq!(param, vec![0, 1, 2, 3, 4, 5], "{mv:?}");
+        }
+    }
+
+    #[test]
+    fn test_moves_toward() {
+        let problem = PermutationProblem::new(Identity);
+        let mut param = vec![3, 0, 1, 2];
+        let guide = vec![0, 1, 2, 3];
+        let mut steps = 0;
+        loop {
+            let moves = problem.moves_toward(&param, &guide).unwrap();
+            let Some(mv) = moves.first() else {
+                break;
+            };
+            mv.apply(&mut param);
+            steps += 1;
+        }
+        assert_eq!(param, guide);
+        assert_eq!(steps, 3);
+    }
+}
@@ -1,7 +1,12 @@
-use crate::solver::Solver;
-use crate::state::State;
+use crate::constraints::Penalized;
+use crate::errors::LocalSearchError;
+use crate::problem::{Constraints, PenaltySnapshot};
+use crate::solver::{GuidedLocalSearch, Solver};
+use crate::state::{ElitePool, IterState, State};
+use rand::Rng;
 use std::cmp::Ordering;
 use std::fmt;
+use std::hash::Hash;
 
 #[derive(Clone)]
 pub struct LocalSearchSolution<O, S, I> {
@@ -36,6 +41,56 @@ impl<O, S, I> LocalSearchSolution<O, S, I> {
     }
 }
 
+impl<O, S, P> LocalSearchSolution<O, S, IterState<P>> {
+    /// The elite params gathered with `crate::Executor::with_elite_pool`.
+    pub fn elite(&self) -> &ElitePool<P> {
+        self.state.get_elite()
+    }
+}
+
+impl<O, S, P, N> LocalSearchSolution<Penalized<O>, S, IterState<P>>
+where
+    O: Constraints<Param = P, Neighbor = N>,
+{
+    /// Whether the best param satisfies the constraints.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there is no best param or its violations cannot be evaluated.
+    pub fn is_feasible(&self) -> Result<bool, LocalSearchError> {
+        let best_param = self
+            .state
+            .best_param
+            .as_ref()
+            .ok_or(LocalSearchError::NotInitialized)?;
+        self.problem.is_feasible(best_param)
+    }
+
+    /// The best feasible param with its cost, which may differ from the best param when
+    /// the penalties let the search go through infeasible params.
+    pub fn best_feasible(&self) -> Option<(&P, f64)> {
+        self.state
+            .get_best_feasible_param()
+            .map(|param| (param, self.state.get_best_feasible_cost()))
+    }
+}
+
+impl<O, R, N, F, I> LocalSearchSolution<O, GuidedLocalSearch<R, N, F>, I>
+where
+    R: Rng,
+    F: Hash + Eq,
+{
+    /// Penalties recorded along the search, see `crate::PenaltyPolicy::snapshot_interval`.
+    pub fn penalty_snapshots(&self) -> &[PenaltySnapshot<F>] {
+        self.solver.penalty_snapshots()
+    }
+
+    /// The `k` most penalized features at the end of the search, with their penalties.
+    pub fn top_penalized_features(&self, k: usize) -> Vec<(&F, f64)> {
+        self.solver.get_penalty().top_features(k)
+    }
+}
+
 impl<O, S, I: State> PartialEq for LocalSearchSolution<O, S, I> {
     fn eq(&self, other: &Self) -> bool {
         (self.state.get_best_cost() - other.state.get_best_cost()).abs() < f64::EPSILON
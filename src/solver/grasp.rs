@@ -0,0 +1,250 @@
+//! Greedy randomized adaptive search procedure: every iteration builds a solution with the
+//! `Constructive` problem, picking each element at random in a restricted candidate list
+//! (RCL), then improves it with an inner local search solver.
+use crate::errors::LocalSearchError;
+use crate::problem::{Constructive, CostFunction};
+use crate::solver::{improve, Solver};
+use crate::{IterState, State};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+#[derive(Clone)]
+pub struct Grasp<F, R> {
+    solver: F,
+    inner_iters: u32,
+    alphas: Vec<f64>,
+    probabilities: Vec<f64>,
+    cost_sums: Vec<f64>,
+    counts: Vec<u32>,
+    period: u32,
+    amplification: f64,
+    rng: R,
+}
+
+impl<F, R: Rng> Grasp<F, R> {
+    /// Each construction is improved by a new solver running for `inner_iters`
+    /// iterations. `solver` builds it from the rng of the GRASP, so that every inner run
+    /// draws new random numbers. The RCL holds the candidates whose greedy cost is within
+    /// `alpha` of the range of greedy costs from the best one: 0 builds greedily, 1 at
+    /// random.
+    pub fn new(solver: F, inner_iters: u32, alpha: f64, rng: R) -> Self {
+        Self {
+            solver,
+            inner_iters,
+            alphas: vec![alpha],
+            probabilities: vec![1.0],
+            cost_sums: vec![0.0],
+            counts: vec![0],
+            period: u32::MAX,
+            amplification: 10.0,
+            rng,
+        }
+    }
+
+    /// Reactive GRASP: draw the alpha of each construction among `alphas`. Every `period`
+    /// iterations, the probability of each alpha is set proportional to the ratio of the
+    /// best cost to the average cost it led to, raised to `amplification`. Alphas not
+    /// tried yet keep the highest probability.
+    ///
+    /// # Panics
+    ///
+    /// Panic if `alphas` is empty.
+    #[must_use]
+    pub fn with_reactive_alphas(mut self, alphas: &[f64], period: u32, amplification: f64) -> Self {
+        assert!(!alphas.is_empty());
+        let num_alphas = alphas.len();
+        self.alphas = alphas.to_vec();
+        self.probabilities = vec![1.0 / num_alphas as f64; num_alphas];
+        self.cost_sums = vec![0.0; num_alphas];
+        self.counts = vec![0; num_alphas];
+        self.period = period.max(1);
+        self.amplification = amplification;
+        self
+    }
+
+    pub fn alphas(&self) -> &[f64] {
+        &self.alphas
+    }
+
+    pub fn alpha_probabilities(&self) -> &[f64] {
+        &self.probabilities
+    }
+
+    fn draw_alpha(&mut self) -> usize {
+        WeightedIndex::new(&self.probabilities).map_or(0, |index| index.sample(&mut self.rng))
+    }
+
+    /// Build a complete solution with the RCL of the given alpha.
+    fn construct<O, P, E>(&mut self, problem: &O, alpha: f64) -> Result<P, LocalSearchError>
+    where
+        O: Constructive<Param = P, Element = E>,
+    {
+        let mut param = problem.empty_solution()?;
+        loop {
+            let mut candidates = problem.candidates(&param)?;
+            if candidates.is_empty() {
+                return Ok(param);
+            }
+            let costs = candidates
+                .iter()
+                .map(|element| problem.greedy_cost(&param, element))
+                .collect::<Result<Vec<f64>, LocalSearchError>>()?;
+            let (min, max) = costs
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &cost| {
+                    (min.min(cost), max.max(cost))
+                });
+            let threshold = min + alpha * (max - min);
+            let rcl: Vec<usize> = (0..costs.len())
+                .filter(|&i| costs[i] <= threshold)
+                .collect();
+            let chosen = rcl[self.rng.gen_range(0..rcl.len())];
+            problem.add_element(&mut param, &candidates.swap_remove(chosen))?;
+        }
+    }
+
+    fn adapt(&mut self, best_cost: f64) {
+        let scale = best_cost.abs().max(1.0);
+        for ((probability, &sum), &count) in self
+            .probabilities
+            .iter_mut()
+            .zip(&self.cost_sums)
+            .zip(&self.counts)
+        {
+            // The ratio is shifted so that it stays defined for non-positive costs
+            *probability = if count == 0 {
+                1.0
+            } else {
+                let average = sum / f64::from(count);
+                (1.0 / (1.0 + (average - best_cost) / scale)).powf(self.amplification)
+            };
+        }
+        let total: f64 = self.probabilities.iter().sum();
+        for probability in &mut self.probabilities {
+            *probability /= total;
+        }
+    }
+}
+
+impl<O, P, E, F, S, R> Solver<O, IterState<P>> for Grasp<F, R>
+where
+    O: CostFunction<Param = P> + Constructive<Param = P, Element = E>,
+    P: Clone,
+    F: FnMut(&mut R) -> S,
+    S: Solver<O, IterState<P>>,
+    R: Rng,
+{
+    const NAME: &'static str = "Grasp";
+
+    /// Without a param, the run starts from a greedy construction.
+    fn init(
+        &mut self,
+        problem: &mut O,
+        mut state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let param = match state.take_param() {
+            Some(param) => param,
+            None => self.construct(problem, 0.0)?,
+        };
+        let cost = problem.cost(&param)?;
+        Ok(state.param(param).cost(cost))
+    }
+
+    fn next_iter(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let index = self.draw_alpha();
+        let param = self.construct(problem, self.alphas[index])?;
+
+        let solver = (self.solver)(&mut self.rng);
+        let (param, cost) = improve(
+            problem,
+            solver,
+            param,
+            self.inner_iters,
+            state.get_target_cost(),
+        )?;
+
+        self.cost_sums[index] += cost;
+        self.counts[index] += 1;
+        if (state.get_iter() + 1).is_multiple_of(self.period) {
+            self.adapt(cost.min(state.get_best_cost()));
+        }
+        Ok(state.param(param).cost(cost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grasp;
+    use crate::errors::LocalSearchError;
+    use crate::problem::Constructive;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Pick 3 distinct values out of 0..10, the greedy cost of a value being itself.
+    struct Smallest;
+
+    impl Constructive for Smallest {
+        type Param = Vec<usize>;
+        type Element = usize;
+
+        fn empty_solution(&self) -> Result<Vec<usize>, LocalSearchError> {
+            Ok(Vec::new())
+        }
+
+        fn candidates(&self, partial: &Vec<usize>) -> Result<Vec<usize>, LocalSearchError> {
+            if partial.len() == 3 {
+                return Ok(Vec::new());
+            }
+            Ok((0..10).filter(|v| !partial.contains(v)).collect())
+        }
+
+        fn greedy_cost(
+            &self,
+            _partial: &Vec<usize>,
+            element: &usize,
+        ) -> Result<f64, LocalSearchError> {
+            Ok(*element as f64)
+        }
+
+        fn add_element(
+            &self,
+            partial: &mut Vec<usize>,
+            element: &usize,
+        ) -> Result<(), LocalSearchError> {
+            partial.push(*element);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_construct() {
+        let mut grasp = Grasp::new((), 0, 0.0, StdRng::seed_from_u64(0));
+        assert_eq!(grasp.construct(&Smallest, 0.0).unwrap(), vec![0, 1, 2]);
+        for _ in 0..10 {
+            let param = grasp.construct(&Smallest, 0.5).unwrap();
+            // The RCL of the first element is 0..=4, then shrinks with the range
+            assert!(param[0] <= 4, "{param:?}");
+        }
+    }
+
+    #[test]
+    fn test_adapt() {
+        let mut grasp = Grasp::new((), 0, 0.0, StdRng::seed_from_u64(0)).with_reactive_alphas(
+            &[0.0, 0.5, 1.0],
+            1,
+            1.0,
+        );
+        grasp.cost_sums = vec![10.0, 30.0, 0.0];
+        grasp.counts = vec![1, 1, 0];
+        grasp.adapt(10.0);
+        let probabilities = grasp.alpha_probabilities();
+        assert!(probabilities[0] > probabilities[1]);
+        // Not tried yet
+        assert_eq!(probabilities[2], probabilities[0]);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+}
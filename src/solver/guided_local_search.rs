@@ -1,21 +1,41 @@
 use crate::errors::LocalSearchError;
-use crate::problem::{AugmentedNeighborhood, CostFunction, Neighborhood, Penalty};
-use crate::solver::Solver;
-use crate::termination::{Reason, Status};
-use crate::IterState;
+use crate::problem::{
+    AugmentedNeighborhood, CostFunction, NeighborStream, Neighborhood, Penalty, PenaltyPolicy,
+    PenaltySnapshot,
+};
+use crate::solver::{verify_best_cost, NeighborSelection, Selector, Solver, Stall, StallPolicy};
+use crate::termination::Status;
+use crate::{IterState, State};
 use rand::Rng;
-use rayon::prelude::*;
 use std::hash::Hash;
+use std::iter::Peekable;
 
 pub struct GuidedLocalSearch<R, N, F: Hash + Eq> {
     num_neighbors: Option<u32>,
-    cur_neighbors: Option<Vec<N>>,
+    cur_neighbors: Option<Peekable<NeighborStream<N>>>,
+    selector: Selector,
     penalty: Penalty<F>,
-    stall_iter_best: u32,
-    stall_iter_best_limit: u32,
+    policy: PenaltyPolicy,
+    snapshots: Vec<PenaltySnapshot<F>>,
+    stall: Stall,
     rng: R,
 }
 
+impl<R: Clone, N: Clone, F: Hash + Eq + Clone> Clone for GuidedLocalSearch<R, N, F> {
+    fn clone(&self) -> Self {
+        Self {
+            num_neighbors: self.num_neighbors,
+            cur_neighbors: None,
+            selector: self.selector.clone(),
+            penalty: self.penalty.clone(),
+            policy: self.policy,
+            snapshots: self.snapshots.clone(),
+            stall: self.stall.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+}
+
 impl<R, N, F> GuidedLocalSearch<R, N, F>
 where
     R: Rng,
@@ -25,9 +45,11 @@ where
         Self {
             num_neighbors,
             cur_neighbors: None,
+            selector: Selector::default(),
             penalty: Penalty::new(alpha),
-            stall_iter_best: 0,
-            stall_iter_best_limit: u32::MAX,
+            policy: PenaltyPolicy::default(),
+            snapshots: Vec::new(),
+            stall: Stall::default(),
             rng,
         }
     }
@@ -46,19 +68,92 @@ where
         self.penalty.calibrate(ratio);
     }
 
+    #[must_use]
+    pub fn with_penalty_policy(mut self, policy: PenaltyPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Penalties recorded along the search, see `PenaltyPolicy::snapshot_interval`.
+    pub fn penalty_snapshots(&self) -> &[PenaltySnapshot<F>] {
+        &self.snapshots
+    }
+
     #[must_use]
     pub fn with_stall_best(mut self, iter: u32) -> Self {
-        self.stall_iter_best = 0;
-        self.stall_iter_best_limit = iter;
+        self.stall.limit(iter);
         self
     }
 
-    fn update_stall_iter(&mut self, new_best: bool) {
-        self.stall_iter_best = if new_best {
-            0
-        } else {
-            self.stall_iter_best + 1
-        };
+    /// Respond to stagnation with `policy` instead of terminating, at most `max_restarts`
+    /// times.
+    #[must_use]
+    pub fn with_stall_policy(mut self, policy: StallPolicy, max_restarts: u32) -> Self {
+        self.stall.policy(policy, max_restarts);
+        self
+    }
+
+    /// Number of responses to stagnation, see `with_stall_policy`.
+    pub fn restarts(&self) -> u32 {
+        self.stall.restarts()
+    }
+
+    #[must_use]
+    pub fn with_selection(mut self, selection: NeighborSelection) -> Self {
+        self.selector = Selector::new(selection);
+        self
+    }
+
+    /// Fast local search: select neighbors with `NeighborSelection::CandidateList`, and
+    /// reactivate the anchors of the penalized features, see
+    /// `AugmentedNeighborhood::feature_anchors`.
+    #[must_use]
+    pub fn with_fast_local_search(self) -> Self {
+        self.with_selection(NeighborSelection::CandidateList)
+    }
+
+    /// Penalize the features of maximum utility, and return the anchors they affect.
+    fn penalize(
+        &mut self,
+        features: Vec<(F, f64)>,
+        anchors: impl Fn(&F) -> Vec<usize>,
+    ) -> Vec<usize> {
+        let selected = self.penalty.max_utility(&features);
+        let mut activated = Vec::new();
+        for (i, (feature, _)) in features.into_iter().enumerate() {
+            if selected.contains(&i) {
+                activated.extend(anchors(&feature));
+                self.penalty.update(feature);
+            }
+        }
+        activated
+    }
+
+    /// Apply the `PenaltyPolicy` at the end of iteration `iter`.
+    fn manage_penalties(&mut self, iter: u32, penalized: bool, new_best: bool)
+    where
+        F: Clone,
+    {
+        let policy = self.policy;
+        if penalized {
+            self.penalty.cap(policy.cap);
+            if let Some(max) = policy.normalize {
+                self.penalty.normalize(max);
+            }
+        }
+        if policy.reset_on_best && new_best {
+            self.penalty.reset();
+        }
+        if policy.decay_interval > 0 && iter.is_multiple_of(policy.decay_interval) {
+            self.penalty.decay(policy.decay);
+        }
+        if policy.snapshot_interval > 0 && iter.is_multiple_of(policy.snapshot_interval) {
+            self.snapshots.push(PenaltySnapshot {
+                iter,
+                lambda: self.penalty.lambda,
+                values: self.penalty.values.clone(),
+            });
+        }
     }
 }
 
@@ -71,8 +166,8 @@ where
         + Sync,
     P: Clone + Send + Sync,
     R: Rng,
-    N: Clone + Send + Sync,
-    F: Hash + Eq + Send + Sync,
+    N: Clone + Send + Sync + 'static,
+    F: Hash + Eq + Clone + Send + Sync,
 {
     const NAME: &'static str = "GuidedLocalSearch";
 
@@ -91,75 +186,108 @@ where
         problem: &mut O,
         mut state: IterState<P>,
     ) -> Result<IterState<P>, LocalSearchError> {
-        let prev_param = state.take_param().unwrap();
+        let mut param = state.take_param().unwrap();
+        let mut cost = state.get_cost();
+        if self
+            .stall
+            .respond(problem, &state, &mut param, &mut cost, None, &mut self.rng)?
+        {
+            self.cur_neighbors = None;
+            return Ok(state.param(param).cost(cost));
+        }
 
         let mut neighbors = match self.cur_neighbors.take() {
             Some(n) => n,
-            None => problem.get_neighbor_moves(&mut self.rng, &prev_param)?,
-        };
-
-        let eval_neighbors = match self.num_neighbors {
-            Some(val) => {
-                if val as usize <= neighbors.len() {
-                    neighbors.drain(..val as usize)
-                } else {
-                    neighbors.drain(..)
-                }
-            }
-            None => neighbors.drain(..),
+            None => problem.neighbor_stream(&mut self.rng, &param)?.peekable(),
         };
 
-        let res = eval_neighbors.par_bridge().map(|neighbor| {
-            problem
-                .get_neighbor_augmented_delta(&prev_param, &neighbor, &self.penalty)
-                .map_or_else(
-                    |_| Err(LocalSearchError::FailGenCandidateState),
-                    |new_cost| Ok((neighbor, new_cost)),
-                )
-        });
-
-        let (neighbor, delta) = res
-            .filter_map(core::result::Result::ok)
-            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        let penalty = &self.penalty;
+        let (neighbor, delta) = self
+            .selector
+            .select(
+                &mut neighbors,
+                self.num_neighbors,
+                &mut self.rng,
+                |neighbor| {
+                    problem
+                        .get_neighbor_augmented_delta(&param, neighbor, penalty)
+                        .ok()
+                },
+                |neighbor| problem.move_anchors(neighbor),
+            )
             .map_or_else(|| (None, 0.0), |(neighbor, cost)| (Some(neighbor), cost));
 
-        let new_state = neighbor.map_or_else(
-            || prev_param.clone(),
-            |n| problem.make_move(&prev_param, &n).unwrap(),
-        );
-
-        let mut accepted = delta.is_sign_negative() && (delta.abs() > f64::EPSILON);
-
-        let original_cost = problem.cost(&new_state)?;
-        let new_best_found = original_cost < state.best_cost;
-        self.update_stall_iter(new_best_found);
+        let accepted = delta.is_sign_negative() && (delta.abs() > f64::EPSILON);
 
-        if neighbors.is_empty() {
+        if neighbors.peek().is_none() {
             self.cur_neighbors = None;
         } else {
             self.cur_neighbors = Some(neighbors);
         }
 
-        if self.cur_neighbors.is_none() && !accepted {
-            problem.update_penalty(&prev_param, &mut self.penalty)?;
-            self.penalty.lambda = self.penalty.alpha * original_cost
-                / problem.number_of_features(&prev_param)? as f64;
-            accepted = true;
+        // Local minimum of the augmented cost: penalize its features and move anyway
+        let forced = self.cur_neighbors.is_none() && !accepted;
+        let num_features = if forced {
+            let features = problem.features(&param)?;
+            if features.is_empty() {
+                problem.update_penalty(&param, &mut self.penalty)?;
+            } else {
+                let anchors = self.penalize(features, |feature| problem.feature_anchors(feature));
+                self.selector.applied(&anchors);
+            }
+            Some(problem.number_of_features(&param)?)
+        } else {
+            None
+        };
+
+        let original_cost = if accepted || forced {
+            if let Some(n) = &neighbor {
+                problem.apply_move(&mut param, n)?;
+            }
+            problem.cost(&param)?
+        } else if let Some(n) = &neighbor {
+            state.get_cost() + problem.get_neighbor_delta(&param, n)?
+        } else {
+            state.get_cost()
+        };
+        let new_best_found = original_cost < state.best_cost;
+        self.stall.update(new_best_found);
+
+        if let Some(num_features) = num_features {
+            self.penalty.lambda = self.penalty.alpha * original_cost / f64::from(num_features);
         }
+        self.manage_penalties(
+            state.get_iter() + 1,
+            num_features.is_some(),
+            (accepted || forced) && new_best_found,
+        );
 
-        if accepted {
+        if accepted || forced {
+            if let Some(n) = &neighbor {
+                self.selector.applied(&problem.move_anchors(n));
+            }
             self.cur_neighbors = None;
-            Ok(state.param(new_state).cost(original_cost))
+            Ok(state.param(param).cost(original_cost))
         } else {
             let prev_cost = state.get_prev_cost();
-            Ok(state.param(prev_param).cost(prev_cost))
+            Ok(state.param(param).cost(prev_cost))
         }
     }
 
+    fn problem_changed(&mut self) {
+        self.cur_neighbors = None;
+        self.stall.iter = 0;
+    }
+
+    fn finalize(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        verify_best_cost(problem, state)
+    }
+
     fn terminate(&mut self) -> Status {
-        if self.stall_iter_best_limit < self.stall_iter_best {
-            return Status::Terminated(Reason::MaxStallBestReached);
-        }
-        Status::NotTerminated
+        self.stall.status()
     }
 }
@@ -0,0 +1,256 @@
+//! Memetic algorithm: a population of params evolves by crossover, every offspring being
+//! improved by an inner local search solver before it may replace a member.
+use crate::errors::LocalSearchError;
+use crate::problem::CostFunction;
+use crate::representation::Crossover;
+use crate::solver::{improve, Solver};
+use crate::{IterState, State};
+use rand::Rng;
+
+/// How the parents of an offspring are drawn from the population.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParentSelection {
+    /// The best of the given number of members drawn at random.
+    Tournament(usize),
+    /// Uniformly at random.
+    Random,
+}
+
+impl Default for ParentSelection {
+    fn default() -> Self {
+        Self::Tournament(2)
+    }
+}
+
+/// Which member an improved offspring replaces, when it is better.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Replacement {
+    /// The worst member of the population.
+    #[default]
+    Worst,
+    /// The worse of its parents.
+    WorstParent,
+}
+
+type Distance<P> = Box<dyn Fn(&P, &P) -> f64>;
+
+pub struct Memetic<P, F, X, R> {
+    population: Vec<(P, f64)>,
+    solver: F,
+    inner_iters: u32,
+    crossover: X,
+    selection: ParentSelection,
+    replacement: Replacement,
+    diversity: Option<(Distance<P>, f64)>,
+    rng: R,
+}
+
+impl<P, F, X, R: Rng> Memetic<P, F, X, R> {
+    /// Evolve `population`, improving its members and every offspring with a new solver
+    /// running for `inner_iters` iterations. `solver` builds it from the rng of the
+    /// memetic algorithm, so that every inner run draws new random numbers. The param of
+    /// the state, if any, joins the population.
+    pub fn new(population: Vec<P>, solver: F, inner_iters: u32, crossover: X, rng: R) -> Self {
+        Self {
+            population: population
+                .into_iter()
+                .map(|param| (param, f64::INFINITY))
+                .collect(),
+            solver,
+            inner_iters,
+            crossover,
+            selection: ParentSelection::default(),
+            replacement: Replacement::default(),
+            diversity: None,
+            rng,
+        }
+    }
+
+    #[must_use]
+    pub fn with_parent_selection(mut self, selection: ParentSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    #[must_use]
+    pub fn with_replacement(mut self, replacement: Replacement) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    /// Keep the members at least `min_distance` apart: an offspring closer than that to
+    /// members can only replace the closest of them, when it is better.
+    #[must_use]
+    pub fn with_diversity<D>(mut self, min_distance: f64, distance: D) -> Self
+    where
+        D: Fn(&P, &P) -> f64 + 'static,
+    {
+        self.diversity = Some((Box::new(distance), min_distance));
+        self
+    }
+
+    /// Members of the population with their costs.
+    pub fn population(&self) -> &[(P, f64)] {
+        &self.population
+    }
+
+    /// Average distance between two members, `None` without a distance.
+    pub fn diversity(&self) -> Option<f64> {
+        let (distance, _) = self.diversity.as_ref()?;
+        let mut sum = 0.0;
+        let mut pairs = 0;
+        for (i, (first, _)) in self.population.iter().enumerate() {
+            for (second, _) in &self.population[i + 1..] {
+                sum += distance(first, second);
+                pairs += 1;
+            }
+        }
+        Some(if pairs == 0 {
+            0.0
+        } else {
+            sum / f64::from(pairs)
+        })
+    }
+
+    fn select_parent(&mut self) -> usize {
+        let size = self.population.len();
+        match self.selection {
+            ParentSelection::Random => self.rng.gen_range(0..size),
+            ParentSelection::Tournament(k) => (0..k.max(1))
+                .map(|_| self.rng.gen_range(0..size))
+                .min_by(|&a, &b| self.population[a].1.total_cmp(&self.population[b].1))
+                .unwrap(),
+        }
+    }
+
+    /// Insert the offspring of `parents` in place of a worse member. Returns whether it
+    /// was inserted.
+    fn replace(&mut self, param: P, cost: f64, parents: (usize, usize)) -> bool {
+        let closest = self
+            .diversity
+            .as_ref()
+            .and_then(|(distance, min_distance)| {
+                self.population
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (member, _))| (i, distance(&param, member)))
+                    .filter(|&(_, d)| d < *min_distance)
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+            });
+        let target = match (closest, self.replacement) {
+            (Some((i, _)), _) => i,
+            (None, Replacement::Worst) => (0..self.population.len())
+                .max_by(|&a, &b| self.population[a].1.total_cmp(&self.population[b].1))
+                .unwrap(),
+            (None, Replacement::WorstParent) => {
+                let (a, b) = parents;
+                if self.population[a].1 >= self.population[b].1 {
+                    a
+                } else {
+                    b
+                }
+            }
+        };
+        if cost < self.population[target].1 {
+            self.population[target] = (param, cost);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<O, P, F, S, X, R> Solver<O, IterState<P>> for Memetic<P, F, X, R>
+where
+    O: CostFunction<Param = P>,
+    P: Clone,
+    F: FnMut(&mut R) -> S,
+    S: Solver<O, IterState<P>>,
+    X: Crossover<P>,
+    R: Rng,
+{
+    const NAME: &'static str = "Memetic";
+
+    fn init(
+        &mut self,
+        problem: &mut O,
+        mut state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        if let Some(param) = state.take_param() {
+            self.population.push((param, f64::INFINITY));
+        }
+        let target_cost = state.get_target_cost();
+        for member in &mut self.population {
+            let param = member.0.clone();
+            let solver = (self.solver)(&mut self.rng);
+            *member = improve(problem, solver, param, self.inner_iters, target_cost)?;
+        }
+        let Some((param, cost)) = self
+            .population
+            .iter()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .cloned()
+        else {
+            return Err(LocalSearchError::NotInitialized);
+        };
+        Ok(state.param(param).cost(cost))
+    }
+
+    fn next_iter(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let parents = (self.select_parent(), self.select_parent());
+        let child = self.crossover.crossover(
+            &self.population[parents.0].0,
+            &self.population[parents.1].0,
+            &mut self.rng,
+        )?;
+        let solver = (self.solver)(&mut self.rng);
+        let (child, cost) = improve(
+            problem,
+            solver,
+            child,
+            self.inner_iters,
+            state.get_target_cost(),
+        )?;
+        self.replace(child.clone(), cost, parents);
+        Ok(state.param(child).cost(cost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Memetic, Replacement};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn memetic(costs: &[f64]) -> Memetic<f64, (), (), StdRng> {
+        let mut memetic = Memetic::new(costs.to_vec(), (), 0, (), StdRng::seed_from_u64(0));
+        for member in &mut memetic.population {
+            member.1 = member.0;
+        }
+        memetic
+    }
+
+    #[test]
+    fn test_replace() {
+        let mut worst = memetic(&[1.0, 5.0, 3.0]);
+        assert!(!worst.replace(6.0, 6.0, (0, 2)));
+        assert!(worst.replace(4.0, 4.0, (0, 2)));
+        assert_eq!(worst.population()[1], (4.0, 4.0));
+
+        let mut parent = memetic(&[1.0, 5.0, 3.0]).with_replacement(Replacement::WorstParent);
+        assert!(parent.replace(2.0, 2.0, (0, 2)));
+        assert_eq!(parent.population()[2], (2.0, 2.0));
+
+        // 2.9 is too close to 3.0 and can only replace it
+        let mut diverse =
+            memetic(&[1.0, 5.0, 3.0]).with_diversity(0.5, |a: &f64, b: &f64| (a - b).abs());
+        assert!(diverse.replace(2.9, 2.9, (0, 1)));
+        assert_eq!(diverse.population()[2], (2.9, 2.9));
+        assert!(!diverse.replace(3.2, 3.2, (0, 1)));
+        assert!(diverse.diversity().unwrap() > 0.0);
+    }
+}
@@ -1,14 +1,30 @@
+pub mod grasp;
 pub mod guided_local_search;
+pub mod memetic;
+pub mod pipeline;
+pub mod reactive_tabu_search;
+pub mod selection;
+pub mod stall;
 pub mod tabu_search;
 pub mod variable_neighborhood;
 
+pub use grasp::Grasp;
 pub use guided_local_search::GuidedLocalSearch;
-pub use tabu_search::TabuSearch;
+pub use memetic::{Memetic, ParentSelection, Replacement};
+pub use pipeline::{Budget, Pipeline, PipelineMode, Stage, StageStats};
+pub use reactive_tabu_search::{hash_param, ReactiveTabuSearch};
+pub use selection::NeighborSelection;
+pub use stall::StallPolicy;
+pub use tabu_search::{LongTermMemory, TabuSearch};
 pub use variable_neighborhood::VariableNeighborhood;
 
 use crate::errors::LocalSearchError;
-use crate::state::State;
+use crate::executor::run_solver;
+use crate::problem::CostFunction;
+use crate::state::{IterState, State};
 use crate::termination::{Reason, Status};
+pub(crate) use selection::Selector;
+pub(crate) use stall::Stall;
 
 pub trait Solver<O, I: State> {
     const NAME: &'static str;
@@ -25,6 +41,20 @@ pub trait Solver<O, I: State> {
     /// Will return `Err` if
     fn next_iter(&mut self, problem: &mut O, state: I) -> Result<I, LocalSearchError>;
 
+    /// Called once after the last iteration, before the solution is returned.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if
+    fn finalize(&mut self, _problem: &mut O, state: I) -> Result<I, LocalSearchError> {
+        Ok(state)
+    }
+
+    /// Called when the problem instance changed during the run, see
+    /// `crate::Executor::with_changes`. Neighbors generated for the previous instance must
+    /// be dropped.
+    fn problem_changed(&mut self) {}
+
     fn terminate_internal(&mut self, state: &I) -> Status {
         let solver_status = self.terminate();
         if solver_status.terminated() {
@@ -43,3 +73,40 @@ pub trait Solver<O, I: State> {
         Status::NotTerminated
     }
 }
+
+/// Replace the best cost by a full evaluation of the best param, so that costs
+/// accumulated from deltas cannot leak into the solution.
+pub(crate) fn verify_best_cost<O, P>(
+    problem: &O,
+    mut state: IterState<P>,
+) -> Result<IterState<P>, LocalSearchError>
+where
+    O: CostFunction<Param = P>,
+{
+    if let Some(best_param) = state.best_param.as_ref() {
+        state.best_cost = problem.cost(best_param)?;
+    }
+    Ok(state)
+}
+
+/// Run `solver` from `param` for at most `max_iters` iterations, for the meta-solvers
+/// improving the params they generate. Returns the best param found with its cost.
+pub(crate) fn improve<O, P, S>(
+    problem: &mut O,
+    mut solver: S,
+    param: P,
+    max_iters: u32,
+    target_cost: f64,
+) -> Result<(P, f64), LocalSearchError>
+where
+    S: Solver<O, IterState<P>>,
+    P: Clone,
+{
+    let state = IterState::new()
+        .param(param)
+        .max_iters(max_iters)
+        .target_cost(target_cost);
+    let mut state = run_solver(problem, &mut solver, state, |_, _, state| Ok(state))?;
+    let cost = state.get_best_cost();
+    Ok((state.take_best_param().unwrap(), cost))
+}
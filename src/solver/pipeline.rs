@@ -0,0 +1,213 @@
+//! Chaining of solvers: a `Pipeline` runs stages in sequence, or in turns as a portfolio,
+//! each stage starting from the best param found so far and running within its own
+//! budget. Each run of a stage is one iteration of the pipeline.
+use crate::errors::LocalSearchError;
+use crate::executor::run_solver;
+use crate::problem::CostFunction;
+use crate::solver::Solver;
+use crate::termination::{Reason, Status};
+use crate::{IterState, State};
+use std::time;
+
+/// A solver run as a stage of a `Pipeline`. Implemented for every solver on `IterState`.
+pub trait Stage<O, P> {
+    fn name(&self) -> &'static str;
+
+    /// Run until `state` terminates, its `max_time` included.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the solver fails.
+    fn run(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError>;
+}
+
+impl<O, P, S> Stage<O, P> for S
+where
+    S: Solver<O, IterState<P>>,
+    P: Clone,
+{
+    fn name(&self) -> &'static str {
+        S::NAME
+    }
+
+    fn run(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let start = time::Instant::now();
+        run_solver(problem, self, state, |_, _, mut state| {
+            let elapsed = start.elapsed();
+            state.time(Some(elapsed));
+            match state.max_time {
+                Some(max_time) if elapsed >= max_time && !state.terminated() => {
+                    Ok(state.terminate_with(Reason::MaxTimeReached))
+                }
+                _ => Ok(state),
+            }
+        })
+    }
+}
+
+/// Termination budget of a stage, for each of its runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Budget {
+    pub max_iters: u32,
+    pub max_time: Option<time::Duration>,
+}
+
+impl Budget {
+    pub fn iters(max_iters: u32) -> Self {
+        Self {
+            max_iters,
+            max_time: None,
+        }
+    }
+
+    pub fn time(max_time: time::Duration) -> Self {
+        Self {
+            max_iters: u32::MAX,
+            max_time: Some(max_time),
+        }
+    }
+
+    #[must_use]
+    pub fn with_time(mut self, max_time: time::Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+}
+
+/// Statistics of one run of a stage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StageStats {
+    pub stage: usize,
+    pub name: &'static str,
+    pub iters: u32,
+    pub start_cost: f64,
+    pub best_cost: f64,
+    pub time: time::Duration,
+    pub status: Status,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PipelineMode {
+    /// Run every stage once, in order.
+    #[default]
+    Sequence,
+    /// Run the stages in turns until the pipeline terminates. The stages keep their
+    /// internal state, e.g. a tabu list, from one turn to the next.
+    RoundRobin,
+}
+
+pub struct Pipeline<O, P> {
+    stages: Vec<(Box<dyn Stage<O, P>>, Budget)>,
+    mode: PipelineMode,
+    runs: usize,
+    stats: Vec<StageStats>,
+}
+
+impl<O, P> Default for Pipeline<O, P> {
+    fn default() -> Self {
+        Self::new(PipelineMode::default())
+    }
+}
+
+impl<O, P> Pipeline<O, P> {
+    pub fn new(mode: PipelineMode) -> Self {
+        Self {
+            stages: Vec::new(),
+            mode,
+            runs: 0,
+            stats: Vec::new(),
+        }
+    }
+
+    /// Add `solver` as the next stage, running within `budget`.
+    #[must_use]
+    pub fn then<S>(mut self, solver: S, budget: Budget) -> Self
+    where
+        S: Stage<O, P> + 'static,
+    {
+        self.stages.push((Box::new(solver), budget));
+        self
+    }
+
+    pub fn num_stages(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Statistics of every run of a stage, in order.
+    pub fn stats(&self) -> &[StageStats] {
+        &self.stats
+    }
+}
+
+impl<O, P> Solver<O, IterState<P>> for Pipeline<O, P>
+where
+    O: CostFunction<Param = P>,
+    P: Clone,
+{
+    const NAME: &'static str = "Pipeline";
+
+    fn init(
+        &mut self,
+        problem: &mut O,
+        mut state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let param = state.take_param().unwrap();
+        let cost = problem.cost(&param)?;
+        Ok(state.param(param).cost(cost))
+    }
+
+    fn next_iter(
+        &mut self,
+        problem: &mut O,
+        mut state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let index = self.runs % self.stages.len();
+        self.runs += 1;
+        let (stage, budget) = &mut self.stages[index];
+
+        // Start from the best param found by the previous stages
+        let (param, start_cost) = match state.get_best_param() {
+            Some(best_param) => (best_param.clone(), state.get_best_cost()),
+            None => (state.take_param().unwrap(), state.get_cost()),
+        };
+        let mut inner = IterState::new()
+            .param(param)
+            .max_iters(budget.max_iters)
+            .target_cost(state.get_target_cost());
+        if let Some(max_time) = budget.max_time {
+            inner = inner.max_time(max_time);
+        }
+
+        let start = time::Instant::now();
+        let mut inner = stage.run(problem, inner)?;
+        let cost = inner.get_best_cost();
+        self.stats.push(StageStats {
+            stage: index,
+            name: stage.name(),
+            iters: inner.get_iter(),
+            start_cost,
+            best_cost: cost,
+            time: start.elapsed(),
+            status: inner.get_termination_status().clone(),
+        });
+        let param = inner.take_best_param().unwrap();
+        Ok(state.param(param).cost(cost))
+    }
+
+    fn terminate(&mut self) -> Status {
+        if self.stages.is_empty()
+            || (self.mode == PipelineMode::Sequence && self.runs >= self.stages.len())
+        {
+            return Status::Terminated(Reason::SolverExit("All stages completed".to_owned()));
+        }
+        Status::NotTerminated
+    }
+}
@@ -0,0 +1,315 @@
+//! Reactive tabu search: the tenure of the tabu list adapts to the search. Visited params
+//! are hashed to detect repetitions, which lengthen the tenure, while the tenure shrinks
+//! back after a while without repetition. When params keep being repeated, the search is
+//! trapped and escapes with a random walk.
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, NeighborStream, Neighborhood};
+use crate::solver::{verify_best_cost, NeighborSelection, Selector, Solver, Stall, StallPolicy};
+use crate::termination::Status;
+use crate::{IterState, State};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+/// Hash of a param with its derived `Hash`, the default hasher of `ReactiveTabuSearch`.
+pub fn hash_param<P: Hash>(param: &P) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    param.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Visit {
+    last_iter: u32,
+    count: u32,
+}
+
+#[derive(Clone)]
+pub struct ReactiveTabuSearch<R, N, H> {
+    num_neighbors: Option<u32>,
+    selector: Selector,
+    hasher: H,
+    tabu_list: VecDeque<N>,
+    tenure: usize,
+    min_tenure: usize,
+    max_tenure: usize,
+    increase: f64,
+    decrease: f64,
+    visited: HashMap<u64, Visit>,
+    last_change: u32,
+    cycle_average: f64,
+    repetitions_limit: u32,
+    chaotic: u32,
+    chaotic_limit: u32,
+    repetitions: u32,
+    escapes: u32,
+    stall: Stall,
+    rng: R,
+}
+
+impl<R: Rng, N, H> ReactiveTabuSearch<R, N, H> {
+    /// `hasher` identifies visited params, e.g. `hash_param` for params deriving `Hash`.
+    pub fn new(num_neighbors: Option<u32>, hasher: H, rng: R) -> Self {
+        Self {
+            num_neighbors,
+            selector: Selector::default(),
+            hasher,
+            tabu_list: VecDeque::new(),
+            tenure: 1,
+            min_tenure: 1,
+            max_tenure: 1000,
+            increase: 1.1,
+            decrease: 0.9,
+            visited: HashMap::new(),
+            last_change: 0,
+            cycle_average: 1.0,
+            repetitions_limit: 3,
+            chaotic: 0,
+            chaotic_limit: 3,
+            repetitions: 0,
+            escapes: 0,
+            stall: Stall::default(),
+            rng,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panic if `min_tenure` is 0 or greater than `max_tenure`.
+    #[must_use]
+    pub fn with_tenure_bounds(mut self, min_tenure: usize, max_tenure: usize) -> Self {
+        assert!(0 < min_tenure && min_tenure <= max_tenure);
+        self.min_tenure = min_tenure;
+        self.max_tenure = max_tenure;
+        self.tenure = self.tenure.clamp(min_tenure, max_tenure);
+        self
+    }
+
+    /// Multipliers of the tenure on a repetition and after a while without repetition.
+    #[must_use]
+    pub fn with_tenure_factors(mut self, increase: f64, decrease: f64) -> Self {
+        self.increase = increase;
+        self.decrease = decrease;
+        self
+    }
+
+    /// Params visited more than `repetitions` times are chaotic, and the search escapes
+    /// once more than `chaotic` of them are found.
+    #[must_use]
+    pub fn with_escape(mut self, repetitions: u32, chaotic: u32) -> Self {
+        self.repetitions_limit = repetitions;
+        self.chaotic_limit = chaotic;
+        self
+    }
+
+    #[must_use]
+    pub fn with_stall_best(mut self, iter: u32) -> Self {
+        self.stall.limit(iter);
+        self
+    }
+
+    /// Respond to stagnation with `policy` instead of terminating, at most `max_restarts`
+    /// times.
+    #[must_use]
+    pub fn with_stall_policy(mut self, policy: StallPolicy, max_restarts: u32) -> Self {
+        self.stall.policy(policy, max_restarts);
+        self
+    }
+
+    /// Number of responses to stagnation, see `with_stall_policy`.
+    pub fn restarts(&self) -> u32 {
+        self.stall.restarts()
+    }
+
+    #[must_use]
+    pub fn with_selection(mut self, selection: NeighborSelection) -> Self {
+        self.selector = Selector::new(selection);
+        self
+    }
+
+    pub fn tenure(&self) -> usize {
+        self.tenure
+    }
+
+    /// Number of visits to an already visited param.
+    pub fn repetitions(&self) -> u32 {
+        self.repetitions
+    }
+
+    pub fn escapes(&self) -> u32 {
+        self.escapes
+    }
+
+    /// Record the visit of the param hashed to `hash` at `iter`, and adapt the tenure.
+    /// Returns whether the search must escape.
+    fn react(&mut self, hash: u64, iter: u32) -> bool {
+        let mut escape = false;
+        match self.visited.get_mut(&hash) {
+            Some(visit) => {
+                let cycle = iter - visit.last_iter;
+                visit.last_iter = iter;
+                visit.count += 1;
+                self.repetitions += 1;
+                if visit.count > self.repetitions_limit {
+                    self.chaotic += 1;
+                    if self.chaotic > self.chaotic_limit {
+                        self.chaotic = 0;
+                        escape = true;
+                    }
+                }
+                self.cycle_average = 0.1 * f64::from(cycle) + 0.9 * self.cycle_average;
+                let increased = (self.tenure as f64 * self.increase).ceil() as usize;
+                self.tenure = increased.max(self.tenure + 1).min(self.max_tenure);
+                self.last_change = iter;
+            }
+            None => {
+                self.visited.insert(
+                    hash,
+                    Visit {
+                        last_iter: iter,
+                        count: 1,
+                    },
+                );
+                if f64::from(iter - self.last_change) > self.cycle_average {
+                    let decreased = (self.tenure as f64 * self.decrease).floor() as usize;
+                    self.tenure = decreased.max(self.min_tenure);
+                    self.last_change = iter;
+                }
+            }
+        }
+        escape
+    }
+}
+
+impl<O, P, R, N, H> Solver<O, IterState<P>> for ReactiveTabuSearch<R, N, H>
+where
+    O: CostFunction<Param = P> + Neighborhood<Param = P, Neighbor = N> + Send + Sync,
+    P: Clone + Send + Sync,
+    R: Rng,
+    N: Clone + PartialEq + Send + Sync + 'static,
+    H: Fn(&P) -> u64,
+{
+    const NAME: &'static str = "ReactiveTabuSearch";
+
+    fn init(
+        &mut self,
+        problem: &mut O,
+        mut state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let param = state.take_param().unwrap();
+        let cost = problem.cost(&param)?;
+        self.react((self.hasher)(&param), state.get_iter());
+        Ok(state.param(param).cost(cost))
+    }
+
+    fn next_iter(
+        &mut self,
+        problem: &mut O,
+        mut state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        let mut param = state.take_param().unwrap();
+        let mut cost = state.get_cost();
+        if self
+            .stall
+            .respond(problem, &state, &mut param, &mut cost, None, &mut self.rng)?
+        {
+            return Ok(state.param(param).cost(cost));
+        }
+        let prev_cost = cost;
+        let best_cost = state.get_best_cost();
+
+        let mut neighbors: NeighborStream<N> = problem.neighbor_stream(&mut self.rng, &param)?;
+        let tabu_list = &self.tabu_list;
+        let selected = self.selector.select(
+            &mut neighbors,
+            self.num_neighbors,
+            &mut self.rng,
+            |neighbor| {
+                let delta = problem.get_neighbor_delta(&param, neighbor).ok()?;
+                // Aspiration: a tabu move leading to a new best param is allowed
+                (prev_cost + delta < best_cost || !tabu_list.contains(neighbor)).then_some(delta)
+            },
+            |neighbor| problem.move_anchors(neighbor),
+        );
+
+        let mut cost = prev_cost;
+        if let Some((neighbor, delta)) = selected {
+            let tabu_move = problem.tabu_move(&param, &neighbor);
+            problem.apply_move(&mut param, &neighbor)?;
+            self.selector.applied(&problem.move_anchors(&neighbor));
+            self.tabu_list.push_back(tabu_move);
+            cost += delta;
+        }
+
+        let iter = state.get_iter() + 1;
+        if self.react((self.hasher)(&param), iter) {
+            // Random walk, longer for longer cycles
+            let steps = 1 + (1.0 + self.cycle_average) as usize / 2;
+            for _ in 0..steps {
+                let moves = problem.get_neighbor_moves(&mut self.rng, &param)?;
+                if let Some(neighbor) = moves.choose(&mut self.rng) {
+                    self.tabu_list
+                        .push_back(problem.tabu_move(&param, neighbor));
+                    problem.apply_move(&mut param, neighbor)?;
+                }
+            }
+            cost = problem.cost(&param)?;
+            self.visited.clear();
+            self.escapes += 1;
+        }
+        while self.tabu_list.len() > self.tenure {
+            self.tabu_list.pop_front();
+        }
+
+        self.stall.update(cost < state.best_cost);
+        Ok(state.param(param).cost(cost))
+    }
+
+    fn problem_changed(&mut self) {
+        self.visited.clear();
+        self.tabu_list.clear();
+        self.stall.iter = 0;
+    }
+
+    fn finalize(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        verify_best_cost(problem, state)
+    }
+
+    fn terminate(&mut self) -> Status {
+        self.stall.status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_param, ReactiveTabuSearch};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_react() {
+        let mut solver: ReactiveTabuSearch<_, (), _> =
+            ReactiveTabuSearch::new(None, hash_param::<u32>, StdRng::seed_from_u64(0))
+                .with_escape(1, 1);
+        assert!(!solver.react(hash_param(&0), 0));
+        assert!(!solver.react(hash_param(&1), 1));
+        // Cycle of length 2, the param is chaotic
+        assert!(!solver.react(hash_param(&0), 2));
+        assert_eq!(solver.tenure(), 2);
+        // Second chaotic param
+        assert!(solver.react(hash_param(&1), 3));
+        assert_eq!(solver.tenure(), 3);
+        assert_eq!(solver.repetitions(), 2);
+        // The tenure decreases after a while without repetition
+        for iter in 5..10 {
+            assert!(!solver.react(hash_param(&iter), iter));
+        }
+        assert!(solver.tenure() < 3);
+    }
+}
@@ -0,0 +1,239 @@
+use rand::Rng;
+use rayon::prelude::*;
+use std::sync::Mutex;
+
+/// How a solver picks the neighbor to move to among the generated neighbors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NeighborSelection {
+    /// Evaluate the next `num_neighbors` neighbors in parallel and keep the best one.
+    #[default]
+    BestImprovement,
+    /// Evaluate the next `num_neighbors` neighbors until one improves on the current param.
+    /// In parallel, the improving neighbor found first is kept and the remaining
+    /// evaluations are cancelled. Without an improving neighbor, the best one is kept.
+    FirstImprovement { parallel: bool },
+    /// Evaluate `k` neighbors drawn at random among the next `num_neighbors` neighbors, or
+    /// all the remaining ones without limit, and keep the best one.
+    RandomSample(usize),
+    /// Best improvement skipping the neighbors anchored on a "don't look" element, see
+    /// `Neighborhood::move_anchors`. An anchor is marked when none of its evaluated
+    /// neighbors improves, and cleared when a move touching it is applied. All bits are
+    /// cleared once every neighbor of a batch is skipped.
+    CandidateList,
+}
+
+/// A `NeighborSelection` with the "don't look" bits it maintains.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Selector {
+    pub(crate) selection: NeighborSelection,
+    dont_look: Vec<bool>,
+}
+
+impl Selector {
+    pub(crate) fn new(selection: NeighborSelection) -> Self {
+        Self {
+            selection,
+            dont_look: Vec::new(),
+        }
+    }
+
+    fn is_dont_look(&self, anchor: usize) -> bool {
+        self.dont_look.get(anchor).copied().unwrap_or(false)
+    }
+
+    fn set_dont_look(&mut self, anchor: usize, value: bool) {
+        if anchor >= self.dont_look.len() {
+            if !value {
+                return;
+            }
+            self.dont_look.resize(anchor + 1, false);
+        }
+        self.dont_look[anchor] = value;
+    }
+
+    /// Clear the "don't look" bits of the anchors of an applied move.
+    pub(crate) fn applied(&mut self, anchors: &[usize]) {
+        for &anchor in anchors {
+            self.set_dont_look(anchor, false);
+        }
+    }
+
+    /// Pull the neighbors to evaluate out of `neighbors`, and return the selected one
+    /// with its delta. `delta` returns `None` for neighbors which cannot be selected,
+    /// e.g. tabu ones or ones failing to evaluate.
+    pub(crate) fn select<N, I, R, D, A>(
+        &mut self,
+        neighbors: &mut I,
+        num_neighbors: Option<u32>,
+        rng: &mut R,
+        delta: D,
+        anchors: A,
+    ) -> Option<(N, f64)>
+    where
+        N: Send,
+        I: Iterator<Item = N> + Send,
+        R: Rng,
+        D: Fn(&N) -> Option<f64> + Sync,
+        A: Fn(&N) -> Vec<usize>,
+    {
+        let batch_size = num_neighbors.map_or(usize::MAX, |val| val as usize);
+        let batch = neighbors.by_ref().take(batch_size);
+        match self.selection {
+            NeighborSelection::BestImprovement => best(batch, &delta),
+            NeighborSelection::FirstImprovement { parallel: false } => {
+                let mut selected: Option<(N, f64)> = None;
+                for neighbor in batch {
+                    let Some(delta) = delta(&neighbor) else {
+                        continue;
+                    };
+                    if is_improving(delta) {
+                        return Some((neighbor, delta));
+                    }
+                    if selected.as_ref().is_none_or(|(_, best)| delta < *best) {
+                        selected = Some((neighbor, delta));
+                    }
+                }
+                selected
+            }
+            NeighborSelection::FirstImprovement { parallel: true } => {
+                let selected: Mutex<Option<(N, f64)>> = Mutex::new(None);
+                batch
+                    .par_bridge()
+                    .find_map_any(|neighbor| {
+                        let delta = delta(&neighbor)?;
+                        if is_improving(delta) {
+                            return Some((neighbor, delta));
+                        }
+                        let mut selected = selected.lock().unwrap();
+                        if selected.as_ref().is_none_or(|(_, best)| delta < *best) {
+                            *selected = Some((neighbor, delta));
+                        }
+                        None
+                    })
+                    .or_else(|| selected.into_inner().unwrap())
+            }
+            NeighborSelection::RandomSample(k) => {
+                // Reservoir sampling, the whole batch is consumed but only `k` neighbors
+                // are kept
+                let mut sample: Vec<N> = Vec::with_capacity(k);
+                for (i, neighbor) in batch.enumerate() {
+                    if i < k {
+                        sample.push(neighbor);
+                    } else {
+                        let j = rng.gen_range(0..=i);
+                        if j < k {
+                            sample[j] = neighbor;
+                        }
+                    }
+                }
+                best(sample.into_iter(), &delta)
+            }
+            NeighborSelection::CandidateList => {
+                let mut batch: Vec<(N, Vec<usize>)> = batch
+                    .map(|neighbor| {
+                        let anchors = anchors(&neighbor);
+                        (neighbor, anchors)
+                    })
+                    .collect();
+                let skipped = |selector: &Self, anchors: &[usize]| {
+                    anchors
+                        .first()
+                        .is_some_and(|&anchor| selector.is_dont_look(anchor))
+                };
+                if batch.iter().all(|(_, anchors)| skipped(self, anchors)) {
+                    self.dont_look.clear();
+                }
+                batch.retain(|(_, anchors)| !skipped(self, anchors));
+                let evaluated: Vec<(N, Vec<usize>, Option<f64>)> = batch
+                    .into_par_iter()
+                    .map(|(neighbor, anchors)| {
+                        let delta = delta(&neighbor);
+                        (neighbor, anchors, delta)
+                    })
+                    .collect();
+                for improving in [false, true] {
+                    for (_, anchors, delta) in &evaluated {
+                        if delta.is_some_and(is_improving) == improving {
+                            if let Some(&anchor) = anchors.first() {
+                                self.set_dont_look(anchor, !improving);
+                            }
+                        }
+                    }
+                }
+                evaluated
+                    .into_iter()
+                    .filter_map(|(neighbor, _, delta)| delta.map(|delta| (neighbor, delta)))
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            }
+        }
+    }
+}
+
+fn is_improving(delta: f64) -> bool {
+    delta.is_sign_negative() && delta.abs() > f64::EPSILON
+}
+
+fn best<N, I, D>(neighbors: I, delta: &D) -> Option<(N, f64)>
+where
+    N: Send,
+    I: Iterator<Item = N> + Send,
+    D: Fn(&N) -> Option<f64> + Sync,
+{
+    neighbors
+        .par_bridge()
+        .filter_map(|neighbor| delta(&neighbor).map(|delta| (neighbor, delta)))
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NeighborSelection, Selector};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn delta(neighbor: &i32) -> Option<f64> {
+        Some(f64::from(*neighbor))
+    }
+
+    #[test]
+    fn test_first_improvement() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut selector = Selector::new(NeighborSelection::FirstImprovement { parallel: false });
+        let mut neighbors = vec![3, -1, -5, 2].into_iter();
+        let selected = selector.select(&mut neighbors, Some(3), &mut rng, delta, |_| vec![]);
+        assert_eq!(selected, Some((-1, -1.0)));
+        assert_eq!(neighbors.collect::<Vec<_>>(), vec![-5, 2]);
+    }
+
+    #[test]
+    fn test_random_sample() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut selector = Selector::new(NeighborSelection::RandomSample(2));
+        let mut neighbors = vec![3, 1, 4, -5, 2].into_iter();
+        let selected = selector.select(&mut neighbors, Some(3), &mut rng, delta, |_| vec![]);
+        assert!(matches!(selected, Some((1 | 3 | 4, _))));
+        assert_eq!(neighbors.collect::<Vec<_>>(), vec![-5, 2]);
+    }
+
+    #[test]
+    fn test_candidate_list() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut selector = Selector::new(NeighborSelection::CandidateList);
+        let anchors = |neighbor: &i32| vec![neighbor.unsigned_abs() as usize % 3];
+        let mut neighbors = vec![1, 2, -3].into_iter();
+        let selected = selector.select(&mut neighbors, None, &mut rng, delta, anchors);
+        assert_eq!(selected, Some((-3, -3.0)));
+        assert!(selector.is_dont_look(1) && selector.is_dont_look(2));
+        assert!(!selector.is_dont_look(0));
+
+        // Neighbors anchored on 1 and 2 are skipped
+        let mut neighbors = vec![4, 5, 6].into_iter();
+        let selected = selector.select(&mut neighbors, None, &mut rng, delta, anchors);
+        assert_eq!(selected, Some((6, 6.0)));
+
+        selector.applied(&[1]);
+        let mut neighbors = vec![4, 5].into_iter();
+        let selected = selector.select(&mut neighbors, None, &mut rng, delta, anchors);
+        assert_eq!(selected, Some((4, 4.0)));
+    }
+}
@@ -0,0 +1,153 @@
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood};
+use crate::termination::{Reason, Status};
+use crate::{IterState, State};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// How a solver responds when it finds no new best param for more iterations than its
+/// `with_stall_best` limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StallPolicy {
+    /// Terminate with `Reason::MaxStallBestReached`.
+    #[default]
+    Terminate,
+    /// Continue from the best param found so far.
+    RestartFromBest,
+    /// Continue from a new param, see `Neighborhood::random_param`.
+    RestartFromRandom,
+    /// Apply the given number of random moves to the current param.
+    Perturb(usize),
+    /// Multiply the temperature of the acceptance criterion by the given factor. Solvers
+    /// without a temperature continue from the current param.
+    IncreaseTemperature(f64),
+}
+
+/// Iterations without a new best, and the restarts they triggered.
+#[derive(Clone, Debug)]
+pub(crate) struct Stall {
+    pub(crate) iter: u32,
+    limit: u32,
+    policy: StallPolicy,
+    max_restarts: u32,
+    restarts: u32,
+}
+
+impl Default for Stall {
+    fn default() -> Self {
+        Self {
+            iter: 0,
+            limit: u32::MAX,
+            policy: StallPolicy::default(),
+            max_restarts: 0,
+            restarts: 0,
+        }
+    }
+}
+
+impl Stall {
+    pub(crate) fn limit(&mut self, limit: u32) {
+        self.iter = 0;
+        self.limit = limit;
+    }
+
+    pub(crate) fn policy(&mut self, policy: StallPolicy, max_restarts: u32) {
+        self.policy = policy;
+        self.max_restarts = max_restarts;
+    }
+
+    pub(crate) fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    pub(crate) fn update(&mut self, new_best: bool) {
+        self.iter = if new_best { 0 } else { self.iter + 1 };
+    }
+
+    fn stalled(&self) -> bool {
+        self.limit < self.iter
+    }
+
+    fn exhausted(&self) -> bool {
+        self.policy == StallPolicy::Terminate || self.max_restarts <= self.restarts
+    }
+
+    pub(crate) fn status(&self) -> Status {
+        if self.stalled() && self.exhausted() {
+            return Status::Terminated(Reason::MaxStallBestReached);
+        }
+        Status::NotTerminated
+    }
+
+    /// Apply the policy to `param` and its `cost` once stalled, multiplying `temperature`
+    /// if any. Returns whether `param` changed.
+    pub(crate) fn respond<O, P, R>(
+        &mut self,
+        problem: &O,
+        state: &IterState<P>,
+        param: &mut P,
+        cost: &mut f64,
+        temperature: Option<&mut f64>,
+        rng: &mut R,
+    ) -> Result<bool, LocalSearchError>
+    where
+        O: CostFunction<Param = P> + Neighborhood<Param = P>,
+        P: Clone,
+        R: Rng,
+    {
+        if !self.stalled() || self.exhausted() {
+            return Ok(false);
+        }
+        self.iter = 0;
+        self.restarts += 1;
+        match self.policy {
+            StallPolicy::Terminate => return Ok(false),
+            StallPolicy::RestartFromBest => {
+                let Some(best) = state.get_best_param() else {
+                    return Ok(false);
+                };
+                *param = best.clone();
+                *cost = state.get_best_cost();
+            }
+            StallPolicy::RestartFromRandom => {
+                *param = problem.random_param(rng)?;
+                *cost = problem.cost(param)?;
+            }
+            StallPolicy::Perturb(strength) => {
+                for _ in 0..strength {
+                    let moves = problem.get_neighbor_moves(rng, param)?;
+                    if let Some(neighbor) = moves.choose(rng) {
+                        problem.apply_move(param, neighbor)?;
+                    }
+                }
+                *cost = problem.cost(param)?;
+            }
+            StallPolicy::IncreaseTemperature(factor) => {
+                if let Some(temperature) = temperature {
+                    *temperature *= factor;
+                }
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stall, StallPolicy};
+
+    #[test]
+    fn test_status() {
+        let mut stall = Stall::default();
+        stall.limit(1);
+        stall.policy(StallPolicy::RestartFromBest, 1);
+        stall.update(false);
+        stall.update(false);
+        assert!(!stall.status().terminated());
+        stall.restarts = 1;
+        assert!(stall.status().terminated());
+        stall.update(true);
+        assert!(!stall.status().terminated());
+    }
+}
@@ -1,39 +1,155 @@
 use crate::errors::LocalSearchError;
-use crate::problem::{CostFunction, Neighborhood};
-use crate::solver::Solver;
-use crate::termination::{Reason, Status};
+use crate::problem::{CostFunction, NeighborStream, Neighborhood};
+use crate::solver::{verify_best_cost, NeighborSelection, Selector, Solver, Stall, StallPolicy};
+use crate::termination::Status;
 use crate::{IterState, State};
 use rand::Rng;
-use rayon::prelude::*;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::iter::Peekable;
+
+/// Long-term frequency memory of `TabuSearch`, see `TabuSearch::with_long_term_memory`.
+/// Each phase starts when the number of iterations without a new best is a multiple of
+/// its threshold, intensification prevailing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LongTermMemory {
+    /// Iterations without a new best before each diversification, 0 to never diversify.
+    pub diversify_after: u32,
+    /// Iterations without a new best before each intensification, 0 to never intensify.
+    pub intensify_after: u32,
+    /// Number of iterations of a phase.
+    pub phase_length: u32,
+    /// While diversifying, the delta of a move is increased by `penalty` times the number
+    /// of times its attribute was applied.
+    pub penalty: f64,
+    /// Number of attributes of moves to a new best most often found, whose tabu moves are
+    /// forbidden while intensifying.
+    pub fixed: usize,
+}
+
+impl Default for LongTermMemory {
+    fn default() -> Self {
+        Self {
+            diversify_after: 50,
+            intensify_after: 200,
+            phase_length: 20,
+            penalty: 1.0,
+            fixed: 3,
+        }
+    }
+}
+
+/// Phase of the long-term memory, with the iteration it ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Normal,
+    Diversify(u32),
+    Intensify(u32),
+}
+
+type Attribute<N> = fn(&N) -> u64;
 
-#[derive(Clone)]
 pub struct TabuSearch<R, N> {
     num_neighbors: Option<u32>,
-    cur_neighbors: Option<Vec<N>>,
+    cur_neighbors: Option<Peekable<NeighborStream<N>>>,
+    selector: Selector,
     tabu_list: VecDeque<N>,
+    aspiration: bool,
+    always_accept: bool,
     init_temp: f64,
-    stall_iter_best: u32,
-    stall_iter_best_limit: u32,
+    memory: Option<(LongTermMemory, Attribute<N>)>,
+    phase: Phase,
+    frequency: HashMap<u64, u32>,
+    elite_frequency: HashMap<u64, u32>,
+    diversifications: u32,
+    intensifications: u32,
+    stall: Stall,
     rng: R,
 }
 
+impl<R: Clone, N: Clone> Clone for TabuSearch<R, N> {
+    fn clone(&self) -> Self {
+        // The capacity bounds the tabu list, a plain clone would shrink it
+        let mut tabu_list = VecDeque::with_capacity(self.tabu_list.capacity());
+        tabu_list.extend(self.tabu_list.iter().cloned());
+        Self {
+            num_neighbors: self.num_neighbors,
+            cur_neighbors: None,
+            selector: self.selector.clone(),
+            tabu_list,
+            aspiration: self.aspiration,
+            always_accept: self.always_accept,
+            init_temp: self.init_temp,
+            memory: self.memory,
+            phase: self.phase,
+            frequency: self.frequency.clone(),
+            elite_frequency: self.elite_frequency.clone(),
+            diversifications: self.diversifications,
+            intensifications: self.intensifications,
+            stall: self.stall.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+}
+
 impl<R: Rng, N> TabuSearch<R, N> {
     pub fn new(num_neighbors: Option<u32>, capacity: usize, rng: R) -> Self {
         Self {
             num_neighbors,
             cur_neighbors: None,
+            selector: Selector::default(),
             tabu_list: VecDeque::with_capacity(capacity),
+            aspiration: false,
+            always_accept: false,
             init_temp: 100.0,
-            stall_iter_best: 0,
-            stall_iter_best_limit: u32::MAX,
+            memory: None,
+            phase: Phase::Normal,
+            frequency: HashMap::new(),
+            elite_frequency: HashMap::new(),
+            diversifications: 0,
+            intensifications: 0,
+            stall: Stall::default(),
             rng,
         }
     }
 
     #[must_use]
     pub fn with_stall_best(mut self, iter: u32) -> Self {
-        self.stall_iter_best_limit = iter;
+        self.stall.limit(iter);
+        self
+    }
+
+    /// Respond to stagnation with `policy` instead of terminating, at most `max_restarts`
+    /// times.
+    #[must_use]
+    pub fn with_stall_policy(mut self, policy: StallPolicy, max_restarts: u32) -> Self {
+        self.stall.policy(policy, max_restarts);
+        self
+    }
+
+    /// Number of responses to stagnation, see `with_stall_policy`.
+    pub fn restarts(&self) -> u32 {
+        self.stall.restarts()
+    }
+
+    #[must_use]
+    pub fn with_selection(mut self, selection: NeighborSelection) -> Self {
+        self.selector = Selector::new(selection);
+        self
+    }
+
+    /// Allow a tabu move when it leads to a cost better than the best found so far.
+    #[must_use]
+    pub fn with_aspiration(mut self, aspiration: bool) -> Self {
+        self.aspiration = aspiration;
+        self
+    }
+
+    /// Always move to the selected neighbor, even when it does not improve, as classic tabu
+    /// search does. Otherwise a non-improving neighbor is accepted with a probability
+    /// decreasing with its delta and the iterations, see `with_init_temp`.
+    #[must_use]
+    pub fn with_always_accept(mut self, always_accept: bool) -> Self {
+        self.always_accept = always_accept;
         self
     }
 
@@ -43,12 +159,57 @@ impl<R: Rng, N> TabuSearch<R, N> {
         self
     }
 
-    fn update_stall_iter(&mut self, new_best: bool) {
-        self.stall_iter_best = if new_best {
-            0
-        } else {
-            self.stall_iter_best + 1
+    /// Track how often move attributes are applied, and alternate diversification and
+    /// intensification phases when the search stalls. `attribute` identifies the attribute
+    /// of a move, e.g. `crate::solver::hash_param`.
+    #[must_use]
+    pub fn with_long_term_memory(
+        mut self,
+        memory: LongTermMemory,
+        attribute: Attribute<N>,
+    ) -> Self {
+        self.memory = Some((memory, attribute));
+        self
+    }
+
+    /// Number of diversification phases started.
+    pub fn diversifications(&self) -> u32 {
+        self.diversifications
+    }
+
+    /// Number of intensification phases started, each restarting from an elite param.
+    pub fn intensifications(&self) -> u32 {
+        self.intensifications
+    }
+
+    /// Update the phase at iteration `iter`. Returns whether an intensification starts.
+    fn start_phase(&mut self, iter: u32) -> bool {
+        let Some((memory, _)) = self.memory else {
+            return false;
         };
+        let stall = self.stall.iter;
+        let end = iter.saturating_add(memory.phase_length);
+        if stall > 0 && memory.intensify_after > 0 && stall.is_multiple_of(memory.intensify_after) {
+            self.phase = Phase::Intensify(end);
+            self.intensifications += 1;
+            return true;
+        }
+        if stall > 0 && memory.diversify_after > 0 && stall.is_multiple_of(memory.diversify_after) {
+            self.phase = Phase::Diversify(end);
+            self.diversifications += 1;
+        } else if matches!(self.phase, Phase::Diversify(end) | Phase::Intensify(end) if end <= iter)
+        {
+            self.phase = Phase::Normal;
+        }
+        false
+    }
+
+    /// Attributes of moves to a new best most often found.
+    fn fixed_attributes(&self, k: usize) -> Vec<u64> {
+        let mut attributes: Vec<(u64, u32)> =
+            self.elite_frequency.iter().map(|(&a, &c)| (a, c)).collect();
+        attributes.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        attributes.into_iter().take(k).map(|(a, _)| a).collect()
     }
 }
 
@@ -57,7 +218,7 @@ where
     O: CostFunction<Param = P> + Neighborhood<Param = P, Neighbor = N> + Send + Sync,
     P: Clone + Send + Sync,
     R: Rng,
-    N: Clone + PartialEq + Send + Sync,
+    N: Clone + PartialEq + Send + Sync + 'static,
 {
     const NAME: &'static str = "TabuSearch";
 
@@ -76,79 +237,177 @@ where
         problem: &mut O,
         mut state: IterState<P>,
     ) -> Result<IterState<P>, LocalSearchError> {
-        let prev_param = state.take_param().unwrap();
-        let prev_cost = state.get_cost();
+        let mut param = state.take_param().unwrap();
+        let mut cost = state.get_cost();
+        if self.stall.respond(
+            problem,
+            &state,
+            &mut param,
+            &mut cost,
+            Some(&mut self.init_temp),
+            &mut self.rng,
+        )? {
+            self.cur_neighbors = None;
+            return Ok(state.param(param).cost(cost));
+        }
+        let mut prev_cost = cost;
+        let best_cost = state.get_best_cost();
 
-        let mut neighbors = match self.cur_neighbors.take() {
-            Some(n) => n,
-            None => problem
-                .get_neighbor_moves(&mut self.rng, &prev_param)?
-                .into_iter()
-                .filter(|neighbor| !self.tabu_list.contains(neighbor))
-                .collect(),
+        if self.start_phase(state.get_iter()) {
+            // Restart from an elite param, or the best one without elite pool
+            let elite = state.get_elite().entries();
+            let restart = if elite.is_empty() {
+                state.get_best_param().cloned()
+            } else {
+                Some(elite[self.rng.gen_range(0..elite.len())].0.clone())
+            };
+            if let Some(restart) = restart {
+                param = restart;
+                prev_cost = problem.cost(&param)?;
+                self.tabu_list.clear();
+                self.cur_neighbors = None;
+            }
+        }
+        let attribute = self.memory.map(|(_, attribute)| attribute);
+        let (penalty, fixed) = match (self.phase, self.memory) {
+            (Phase::Diversify(_), Some((memory, _))) => (memory.penalty, Vec::new()),
+            (Phase::Intensify(_), Some((memory, _))) => (0.0, self.fixed_attributes(memory.fixed)),
+            _ => (0.0, Vec::new()),
         };
+        let frequency = &self.frequency;
 
-        let eval_neighbors = match self.num_neighbors {
-            Some(val) => {
-                if val as usize <= neighbors.len() {
-                    neighbors.drain(..val as usize)
-                } else {
-                    neighbors.drain(..)
-                }
-            }
-            None => neighbors.drain(..),
+        let mut neighbors = match self.cur_neighbors.take() {
+            Some(n) => n,
+            None => problem.neighbor_stream(&mut self.rng, &param)?.peekable(),
         };
 
-        let res = eval_neighbors.par_bridge().map(|neighbor| {
-            problem
-                .get_neighbor_delta(&prev_param, &neighbor)
-                .map_or_else(
-                    |_| Err(LocalSearchError::FailGenCandidateState),
-                    |new_cost| Ok((neighbor, new_cost)),
-                )
-        });
-
-        let (neighbor, delta) = res
-            .filter_map(core::result::Result::ok)
-            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        let (tabu_list, aspiration) = (&self.tabu_list, self.aspiration);
+        let (neighbor, delta) = self
+            .selector
+            .select(
+                &mut neighbors,
+                self.num_neighbors,
+                &mut self.rng,
+                |neighbor| {
+                    let delta = problem.get_neighbor_delta(&param, neighbor).ok()?;
+                    let mut selection_delta = delta;
+                    if let Some(attribute) = attribute {
+                        let key = attribute(neighbor);
+                        if fixed.contains(&key) {
+                            return None;
+                        }
+                        let count = frequency.get(&key).copied().unwrap_or(0);
+                        selection_delta += penalty * f64::from(count);
+                    }
+                    ((aspiration && prev_cost + delta < best_cost) || !tabu_list.contains(neighbor))
+                        .then_some(selection_delta)
+                },
+                |neighbor| problem.move_anchors(neighbor),
+            )
             .map_or_else(|| (None, 0.0), |(neighbor, cost)| (Some(neighbor), cost));
 
-        let new_state = if let Some(n) = neighbor {
+        let mut tabu_attribute = None;
+        if let Some(n) = &neighbor {
             if self.tabu_list.len() == self.tabu_list.capacity() {
                 self.tabu_list.pop_front();
             }
-            self.tabu_list.push_back(n.clone());
-            problem.make_move(&prev_param, &n).unwrap()
-        } else {
-            prev_param.clone()
-        };
+            let tabu_move = problem.tabu_move(&param, n);
+            tabu_attribute = attribute.map(|attribute| attribute(&tabu_move));
+            self.tabu_list.push_back(tabu_move);
+        }
 
-        let accepted = (delta.is_sign_negative() && (delta.abs() > f64::EPSILON))
+        let accepted = self.always_accept
+            || (delta.is_sign_negative() && (delta.abs() > f64::EPSILON))
             || (1.0 / (1.0 + f64::from(state.get_iter() + 1).powf(delta / self.init_temp))
                 > self.rng.gen());
 
-        let new_cost = prev_cost + delta;
+        // The selection delta is penalized while diversifying
+        let new_cost = match &neighbor {
+            Some(n) if penalty != 0.0 => prev_cost + problem.get_neighbor_delta(&param, n)?,
+            _ => prev_cost + delta,
+        };
         let new_best_found = new_cost < state.best_cost;
-        self.update_stall_iter(new_best_found);
+        self.stall.update(new_best_found);
 
-        if neighbors.is_empty() {
+        if neighbors.peek().is_none() {
             self.cur_neighbors = None;
         } else {
             self.cur_neighbors = Some(neighbors);
         }
 
         if accepted {
+            if let Some(n) = &neighbor {
+                problem.apply_move(&mut param, n)?;
+                self.selector.applied(&problem.move_anchors(n));
+                if let Some(attribute) = attribute {
+                    *self.frequency.entry(attribute(n)).or_insert(0) += 1;
+                }
+                if let (Some(key), true) = (tabu_attribute, new_best_found) {
+                    *self.elite_frequency.entry(key).or_insert(0) += 1;
+                }
+            }
             self.cur_neighbors = None;
-            Ok(state.param(new_state).cost(new_cost))
+            Ok(state.param(param).cost(new_cost))
         } else {
-            Ok(state.param(prev_param).cost(prev_cost))
+            Ok(state.param(param).cost(prev_cost))
         }
     }
 
+    fn problem_changed(&mut self) {
+        self.cur_neighbors = None;
+        self.tabu_list.clear();
+        self.phase = Phase::Normal;
+        self.frequency.clear();
+        self.elite_frequency.clear();
+        self.stall.iter = 0;
+    }
+
+    fn finalize(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        verify_best_cost(problem, state)
+    }
+
     fn terminate(&mut self) -> Status {
-        if self.stall_iter_best_limit < self.stall_iter_best {
-            return Status::Terminated(Reason::MaxStallBestReached);
-        }
-        Status::NotTerminated
+        self.stall.status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LongTermMemory, Phase, TabuSearch};
+    use crate::solver::hash_param;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_start_phase() {
+        let memory = LongTermMemory {
+            diversify_after: 2,
+            intensify_after: 4,
+            phase_length: 1,
+            ..LongTermMemory::default()
+        };
+        let mut solver: TabuSearch<_, u32> = TabuSearch::new(None, 5, StdRng::seed_from_u64(0))
+            .with_long_term_memory(memory, hash_param);
+        assert!(!solver.start_phase(0));
+        assert_eq!(solver.phase, Phase::Normal);
+        solver.stall.iter = 2;
+        assert!(!solver.start_phase(2));
+        assert_eq!(solver.phase, Phase::Diversify(3));
+        solver.stall.iter = 3;
+        assert!(!solver.start_phase(3));
+        assert_eq!(solver.phase, Phase::Normal);
+        solver.stall.iter = 4;
+        assert!(solver.start_phase(4));
+        assert_eq!(
+            (solver.diversifications(), solver.intensifications()),
+            (1, 1)
+        );
+
+        solver.elite_frequency.extend([(1, 3), (2, 5), (3, 1)]);
+        assert_eq!(solver.fixed_attributes(2), vec![2, 1]);
     }
 }
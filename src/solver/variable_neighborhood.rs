@@ -1,51 +1,74 @@
 use crate::errors::LocalSearchError;
-use crate::problem::{CostFunction, Neighborhood};
-use crate::solver::Solver;
-use crate::termination::{Reason, Status};
+use crate::problem::{CostFunction, NeighborStream, Neighborhood};
+use crate::solver::{verify_best_cost, NeighborSelection, Selector, Solver, Stall, StallPolicy};
+use crate::termination::Status;
 use crate::{IterState, State};
 use rand::Rng;
-use rayon::prelude::*;
+use std::iter::Peekable;
 
-#[derive(Clone)]
 pub struct VariableNeighborhood<R, N> {
     num_neighbors: Option<u32>,
-    cur_neighbors: Option<Vec<N>>,
+    cur_neighbors: Option<Peekable<NeighborStream<N>>>,
+    selector: Selector,
     init_temp: f64,
-    stall_iter_best: u32,
-    stall_iter_best_limit: u32,
+    stall: Stall,
     rng: R,
 }
 
+impl<R: Clone, N: Clone> Clone for VariableNeighborhood<R, N> {
+    fn clone(&self) -> Self {
+        Self {
+            num_neighbors: self.num_neighbors,
+            cur_neighbors: None,
+            selector: self.selector.clone(),
+            init_temp: self.init_temp,
+            stall: self.stall.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+}
+
 impl<R: Rng, N> VariableNeighborhood<R, N> {
     pub fn new(num_neighbors: Option<u32>, rng: R) -> Self {
         Self {
             num_neighbors,
             cur_neighbors: None,
+            selector: Selector::default(),
             init_temp: 100.0,
-            stall_iter_best: 0,
-            stall_iter_best_limit: u32::MAX,
+            stall: Stall::default(),
             rng,
         }
     }
 
     #[must_use]
     pub fn with_stall_best(mut self, iter: u32) -> Self {
-        self.stall_iter_best_limit = iter;
+        self.stall.limit(iter);
         self
     }
 
+    /// Respond to stagnation with `policy` instead of terminating, at most `max_restarts`
+    /// times.
     #[must_use]
-    pub fn with_init_temp(mut self, init_temp: f64) -> Self {
-        self.init_temp = init_temp;
+    pub fn with_stall_policy(mut self, policy: StallPolicy, max_restarts: u32) -> Self {
+        self.stall.policy(policy, max_restarts);
         self
     }
 
-    fn update_stall_iter(&mut self, new_best: bool) {
-        self.stall_iter_best = if new_best {
-            0
-        } else {
-            self.stall_iter_best + 1
-        };
+    /// Number of responses to stagnation, see `with_stall_policy`.
+    pub fn restarts(&self) -> u32 {
+        self.stall.restarts()
+    }
+
+    #[must_use]
+    pub fn with_selection(mut self, selection: NeighborSelection) -> Self {
+        self.selector = Selector::new(selection);
+        self
+    }
+
+    #[must_use]
+    pub fn with_init_temp(mut self, init_temp: f64) -> Self {
+        self.init_temp = init_temp;
+        self
     }
 }
 
@@ -54,7 +77,7 @@ where
     O: CostFunction<Param = P> + Neighborhood<Param = P, Neighbor = N> + Send + Sync,
     P: Clone + Send + Sync,
     R: Rng,
-    N: Clone + Send + Sync,
+    N: Clone + Send + Sync + 'static,
 {
     const NAME: &'static str = "VariableNeighborhood";
 
@@ -73,69 +96,76 @@ where
         problem: &mut O,
         mut state: IterState<P>,
     ) -> Result<IterState<P>, LocalSearchError> {
-        let prev_param = state.take_param().unwrap();
-        let prev_cost = state.get_cost();
+        let mut param = state.take_param().unwrap();
+        let mut cost = state.get_cost();
+        if self.stall.respond(
+            problem,
+            &state,
+            &mut param,
+            &mut cost,
+            Some(&mut self.init_temp),
+            &mut self.rng,
+        )? {
+            self.cur_neighbors = None;
+            return Ok(state.param(param).cost(cost));
+        }
+        let prev_cost = cost;
 
         let mut neighbors = match self.cur_neighbors.take() {
             Some(n) => n,
-            None => problem.get_neighbor_moves(&mut self.rng, &prev_param)?,
+            None => problem.neighbor_stream(&mut self.rng, &param)?.peekable(),
         };
 
-        let eval_neighbors = match self.num_neighbors {
-            Some(val) => {
-                if val as usize <= neighbors.len() {
-                    neighbors.drain(..val as usize)
-                } else {
-                    neighbors.drain(..)
-                }
-            }
-            None => neighbors.drain(..),
-        };
-
-        let res = eval_neighbors.par_bridge().map(|neighbor| {
-            problem
-                .get_neighbor_delta(&prev_param, &neighbor)
-                .map_or_else(
-                    |_| Err(LocalSearchError::FailGenCandidateState),
-                    |new_cost| Ok((neighbor, new_cost)),
-                )
-        });
-
-        let (neighbor, delta) = res
-            .filter_map(core::result::Result::ok)
-            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        let (neighbor, delta) = self
+            .selector
+            .select(
+                &mut neighbors,
+                self.num_neighbors,
+                &mut self.rng,
+                |neighbor| problem.get_neighbor_delta(&param, neighbor).ok(),
+                |neighbor| problem.move_anchors(neighbor),
+            )
             .map_or_else(|| (None, 0.0), |(neighbor, cost)| (Some(neighbor), cost));
 
-        let new_state = neighbor.map_or_else(
-            || prev_param.clone(),
-            |n| problem.make_move(&prev_param, &n).unwrap(),
-        );
-
         let accepted = (delta.is_sign_negative() && (delta.abs() > f64::EPSILON))
             || (1.0 / (1.0 + f64::from(state.get_iter() + 1).powf(delta / self.init_temp))
                 > self.rng.gen());
 
         let new_cost = prev_cost + delta;
         let new_best_found = new_cost < state.best_cost;
-        self.update_stall_iter(new_best_found);
+        self.stall.update(new_best_found);
 
         if accepted {
+            if let Some(n) = &neighbor {
+                problem.apply_move(&mut param, n)?;
+                self.selector.applied(&problem.move_anchors(n));
+            }
             self.cur_neighbors = None;
-            Ok(state.param(new_state).cost(new_cost))
+            Ok(state.param(param).cost(new_cost))
         } else {
-            if neighbors.is_empty() {
+            if neighbors.peek().is_none() {
                 self.cur_neighbors = None;
             } else {
                 self.cur_neighbors = Some(neighbors);
             }
-            Ok(state.param(prev_param).cost(prev_cost))
+            Ok(state.param(param).cost(prev_cost))
         }
     }
 
+    fn problem_changed(&mut self) {
+        self.cur_neighbors = None;
+        self.stall.iter = 0;
+    }
+
+    fn finalize(
+        &mut self,
+        problem: &mut O,
+        state: IterState<P>,
+    ) -> Result<IterState<P>, LocalSearchError> {
+        verify_best_cost(problem, state)
+    }
+
     fn terminate(&mut self) -> Status {
-        if self.stall_iter_best_limit < self.stall_iter_best {
-            return Status::Terminated(Reason::MaxStallBestReached);
-        }
-        Status::NotTerminated
+        self.stall.status()
     }
 }
@@ -0,0 +1,158 @@
+use crate::errors::LocalSearchError;
+
+/// The best params found, kept at a minimum distance from each other so that they cover
+/// different regions of the search space. Entries are sorted by increasing cost.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElitePool<P> {
+    capacity: usize,
+    min_distance: f64,
+    entries: Vec<(P, f64)>,
+}
+
+/// An empty pool of capacity 0, which accepts nothing.
+impl<P> Default for ElitePool<P> {
+    fn default() -> Self {
+        Self::new(0, 0.0)
+    }
+}
+
+impl<P> ElitePool<P> {
+    pub fn new(capacity: usize, min_distance: f64) -> Self {
+        Self {
+            capacity,
+            min_distance,
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[(P, f64)] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> Option<&(P, f64)> {
+        self.entries.first()
+    }
+
+    /// Whether `param` with `cost` would enter the pool, see `insert`.
+    pub fn accepts<D>(&self, param: &P, cost: f64, distance: D) -> bool
+    where
+        D: Fn(&P, &P) -> f64,
+    {
+        self.replaced(param, cost, &distance).is_some()
+    }
+
+    /// Insert `param` if it improves the pool. A param closer than the minimum distance
+    /// to entries only replaces the closest of them, if it is better. Otherwise it is
+    /// added when the pool is not full, or replaces the worst entry if it is better.
+    /// Returns whether it was inserted.
+    pub fn insert<D>(&mut self, param: P, cost: f64, distance: D) -> bool
+    where
+        D: Fn(&P, &P) -> f64,
+    {
+        let Some(index) = self.replaced(&param, cost, &distance) else {
+            return false;
+        };
+        if index < self.entries.len() {
+            self.entries.remove(index);
+        }
+        let position = self.entries.partition_point(|(_, c)| *c <= cost);
+        self.entries.insert(position, (param, cost));
+        true
+    }
+
+    /// Update every entry with `update`, which may modify the param and returns its new
+    /// cost from the current one, e.g. after a change of the instance. The entries are
+    /// sorted again, but may end up closer than the minimum distance.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `update` fails.
+    pub fn update<F>(&mut self, mut update: F) -> Result<(), LocalSearchError>
+    where
+        F: FnMut(&mut P, f64) -> Result<f64, LocalSearchError>,
+    {
+        for (param, cost) in &mut self.entries {
+            *cost = update(param, *cost)?;
+        }
+        self.entries.sort_by(|a, b| a.1.total_cmp(&b.1));
+        Ok(())
+    }
+
+    /// Index of the entry `param` replaces, equal to the length when it is added.
+    fn replaced<D>(&self, param: &P, cost: f64, distance: &D) -> Option<usize>
+    where
+        D: Fn(&P, &P) -> f64,
+    {
+        if self.capacity == 0 {
+            return None;
+        }
+        let closest = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, (entry, _))| (i, distance(param, entry)))
+            .filter(|&(_, d)| d < self.min_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match closest {
+            Some((i, _)) => (cost < self.entries[i].1).then_some(i),
+            None if self.entries.len() < self.capacity => Some(self.entries.len()),
+            None => {
+                let worst = self.entries.len() - 1;
+                (cost < self.entries[worst].1).then_some(worst)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ElitePool;
+
+    fn distance(a: &f64, b: &f64) -> f64 {
+        (a - b).abs()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut pool = ElitePool::new(3, 1.0);
+        assert!(pool.insert(0.0, 5.0, distance));
+        assert!(pool.insert(10.0, 3.0, distance));
+        // Too close to 0.0 and worse
+        assert!(!pool.insert(0.5, 6.0, distance));
+        // Too close to 0.0 and better, replaces it
+        assert!(pool.insert(0.5, 4.0, distance));
+        assert!(pool.insert(20.0, 7.0, distance));
+        assert_eq!(pool.entries(), &[(10.0, 3.0), (0.5, 4.0), (20.0, 7.0)]);
+        // Full, replaces the worst
+        assert!(!pool.insert(30.0, 8.0, distance));
+        assert!(pool.insert(30.0, 1.0, distance));
+        assert_eq!(pool.best(), Some(&(30.0, 1.0)));
+        assert_eq!(pool.len(), 3);
+        assert!(!pool.entries().iter().any(|(p, _)| *p == 20.0));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut pool = ElitePool::new(3, 1.0);
+        pool.insert(0.0, 1.0, distance);
+        pool.insert(10.0, 2.0, distance);
+        pool.update(|param, cost| {
+            *param += 1.0;
+            Ok(-*param - cost)
+        })
+        .unwrap();
+        assert_eq!(pool.entries(), &[(11.0, -13.0), (1.0, -2.0)]);
+    }
+}
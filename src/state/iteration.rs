@@ -1,4 +1,4 @@
-use crate::state::State;
+use crate::state::{ElitePool, State};
 use crate::termination::{Reason, Status};
 use std::mem;
 use std::time;
@@ -20,6 +20,15 @@ pub struct IterState<P> {
     pub time: Option<std::time::Duration>,
     pub max_time: Option<std::time::Duration>,
     pub termination_status: Status,
+    /// Whether the current param satisfies the constraints, when they are tracked.
+    pub feasible: Option<bool>,
+    pub best_feasible_param: Option<P>,
+    pub best_feasible_cost: f64,
+    pub elite: ElitePool<P>,
+    /// Number of cost drifts recorded by `crate::Executor::with_resync`.
+    pub drifts: u32,
+    /// Tracked minus evaluated cost of the last recorded drift.
+    pub last_drift: Option<f64>,
 }
 
 impl<P> IterState<P> {
@@ -86,6 +95,51 @@ impl<P> IterState<P> {
     pub fn take_prev_best_param(&mut self) -> Option<P> {
         self.prev_best_param.take()
     }
+
+    pub fn is_feasible(&self) -> Option<bool> {
+        self.feasible
+    }
+
+    pub fn get_best_feasible_param(&self) -> Option<&P> {
+        self.best_feasible_param.as_ref()
+    }
+
+    pub fn get_best_feasible_cost(&self) -> f64 {
+        self.best_feasible_cost
+    }
+
+    pub fn get_elite(&self) -> &ElitePool<P> {
+        &self.elite
+    }
+
+    pub fn take_elite(&mut self) -> ElitePool<P> {
+        std::mem::take(&mut self.elite)
+    }
+
+    pub fn get_drifts(&self) -> u32 {
+        self.drifts
+    }
+
+    pub fn get_last_drift(&self) -> Option<f64> {
+        self.last_drift
+    }
+}
+
+impl<P: Clone> IterState<P> {
+    /// Record the feasibility of the current param, and keep it as the best feasible
+    /// param if it is.
+    pub fn feasibility(&mut self, feasible: bool) {
+        self.feasible = Some(feasible);
+        if feasible && self.cost < self.best_feasible_cost {
+            if let Some(param) = self.param.as_ref() {
+                match self.best_feasible_param.as_mut() {
+                    Some(best_param) => best_param.clone_from(param),
+                    None => self.best_feasible_param = Some(param.clone()),
+                }
+                self.best_feasible_cost = self.cost;
+            }
+        }
+    }
 }
 
 impl<P: Clone> State for IterState<P> {
@@ -108,6 +162,12 @@ impl<P: Clone> State for IterState<P> {
             time: Some(time::Duration::new(0, 0)),
             max_time: Some(time::Duration::MAX),
             termination_status: Status::NotTerminated,
+            feasible: None,
+            best_feasible_param: None,
+            best_feasible_cost: f64::INFINITY,
+            elite: ElitePool::default(),
+            drifts: 0,
+            last_drift: None,
         }
     }
 
@@ -119,9 +179,13 @@ impl<P: Clone> State for IterState<P> {
                 == self.best_cost.is_sign_positive())
         {
             // If there is no parameter vector, then also don't set the best param.
-            if let Some(param) = self.param.as_ref().cloned() {
+            if let Some(param) = self.param.as_ref() {
                 mem::swap(&mut self.prev_best_param, &mut self.best_param);
-                self.best_param = Some(param);
+                // Reuse the allocation of the former previous best param
+                match self.best_param.as_mut() {
+                    Some(best_param) => best_param.clone_from(param),
+                    None => self.best_param = Some(param.clone()),
+                }
             }
             mem::swap(&mut self.prev_best_cost, &mut self.best_cost);
             self.best_cost = self.cost;
@@ -1,6 +1,8 @@
+pub mod elite;
 pub mod iteration;
 
 use crate::termination::{Reason, Status};
+pub use elite::ElitePool;
 pub use iteration::IterState;
 use std::time;
 
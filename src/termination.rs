@@ -1,5 +1,10 @@
-
+use crate::state::State;
+use cpu_time::ProcessTime;
+use std::collections::VecDeque;
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
 pub enum Status {
@@ -29,6 +34,10 @@ pub enum Reason {
     MaxItersReached,
     MaxTimeReached,
     MaxStallBestReached,
+    MaxCpuTimeReached,
+    MaxStallTimeReached,
+    MaxEvaluationsReached,
+    InsufficientImprovement,
     TargetCostReached,
     KeyboardInterrupt,
     SolverConverged,
@@ -41,6 +50,10 @@ impl fmt::Display for Reason {
             Self::MaxItersReached => "Maximum number of iterations reached",
             Self::MaxTimeReached => "Maximum time reached",
             Self::MaxStallBestReached => "Maximum stall best reached",
+            Self::MaxCpuTimeReached => "Maximum CPU time reached",
+            Self::MaxStallTimeReached => "Maximum stall time reached",
+            Self::MaxEvaluationsReached => "Maximum number of evaluations reached",
+            Self::InsufficientImprovement => "Relative improvement below threshold",
             Self::TargetCostReached => "Target cost value reached",
             Self::KeyboardInterrupt => "Keyboard interrupt",
             Self::SolverConverged => "Solver converged",
@@ -55,3 +68,399 @@ impl Default for Reason {
         Self::SolverExit("Undefined".to_owned())
     }
 }
+
+/// A condition ending a run, checked by `crate::Executor::with_termination` after the
+/// initialization and after every iteration. Closures `FnMut(&I) -> Status` are criteria.
+pub trait TerminationCriterion<I> {
+    fn check(&mut self, state: &I) -> Status;
+
+    /// Terminate as soon as `self` or `other` does.
+    fn or<C>(self, other: C) -> Any<I>
+    where
+        Self: Sized + Send + 'static,
+        C: TerminationCriterion<I> + Send + 'static,
+    {
+        Any(vec![Box::new(self), Box::new(other)])
+    }
+
+    /// Terminate once both `self` and `other` do.
+    fn and<C>(self, other: C) -> All<I>
+    where
+        Self: Sized + Send + 'static,
+        C: TerminationCriterion<I> + Send + 'static,
+    {
+        All(vec![Box::new(self), Box::new(other)])
+    }
+}
+
+impl<I, F> TerminationCriterion<I> for F
+where
+    F: FnMut(&I) -> Status,
+{
+    fn check(&mut self, state: &I) -> Status {
+        self(state)
+    }
+}
+
+/// Terminate with the reason of the first criterion terminating.
+pub struct Any<I>(pub Vec<Box<dyn TerminationCriterion<I> + Send>>);
+
+impl<I> TerminationCriterion<I> for Any<I> {
+    fn check(&mut self, state: &I) -> Status {
+        self.0
+            .iter_mut()
+            .map(|criterion| criterion.check(state))
+            .find(Status::terminated)
+            .unwrap_or_default()
+    }
+}
+
+/// Terminate with the reason of the first criterion once all of them terminate. Every
+/// criterion is checked each time, to keep their memory up to date.
+pub struct All<I>(pub Vec<Box<dyn TerminationCriterion<I> + Send>>);
+
+impl<I> TerminationCriterion<I> for All<I> {
+    fn check(&mut self, state: &I) -> Status {
+        let statuses: Vec<Status> = self
+            .0
+            .iter_mut()
+            .map(|criterion| criterion.check(state))
+            .collect();
+        if !statuses.is_empty() && statuses.iter().all(Status::terminated) {
+            return statuses.into_iter().next().unwrap();
+        }
+        Status::NotTerminated
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaxIters(pub u32);
+
+impl<I: State> TerminationCriterion<I> for MaxIters {
+    fn check(&mut self, state: &I) -> Status {
+        if self.0 <= state.get_iter() {
+            return Status::Terminated(Reason::MaxItersReached);
+        }
+        Status::NotTerminated
+    }
+}
+
+/// Wall time since the start of the run, as set in the state by `crate::Executor`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WallTime(pub Duration);
+
+impl<I: State> TerminationCriterion<I> for WallTime {
+    fn check(&mut self, state: &I) -> Status {
+        match state.get_time() {
+            Some(time) if self.0 <= time => Status::Terminated(Reason::MaxTimeReached),
+            _ => Status::NotTerminated,
+        }
+    }
+}
+
+/// CPU time consumed by the process since the criterion was first checked, all threads
+/// included. Checking it panics if the platform cannot measure the CPU time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CpuTime {
+    max: Duration,
+    start: Option<ProcessTime>,
+}
+
+impl CpuTime {
+    pub fn new(max: Duration) -> Self {
+        Self { max, start: None }
+    }
+}
+
+impl<I: State> TerminationCriterion<I> for CpuTime {
+    fn check(&mut self, _state: &I) -> Status {
+        let start = *self.start.get_or_insert_with(ProcessTime::now);
+        if self.max <= start.elapsed() {
+            return Status::Terminated(Reason::MaxCpuTimeReached);
+        }
+        Status::NotTerminated
+    }
+}
+
+/// Iterations without a new best, counted like the `with_stall_best` limit of the
+/// solvers: the run terminates once there are more than `max` of them in a row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StallIters {
+    max: u32,
+    best_cost: f64,
+    stalled: u32,
+}
+
+impl StallIters {
+    pub fn new(max: u32) -> Self {
+        Self {
+            max,
+            best_cost: f64::INFINITY,
+            stalled: 0,
+        }
+    }
+}
+
+impl<I: State> TerminationCriterion<I> for StallIters {
+    fn check(&mut self, state: &I) -> Status {
+        if state.get_best_cost() < self.best_cost {
+            self.best_cost = state.get_best_cost();
+            self.stalled = 0;
+        } else {
+            self.stalled += 1;
+        }
+        if self.max < self.stalled {
+            return Status::Terminated(Reason::MaxStallBestReached);
+        }
+        Status::NotTerminated
+    }
+}
+
+/// Wall time since the last new best.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StallTime {
+    max: Duration,
+    best_cost: f64,
+    best_time: Duration,
+}
+
+impl StallTime {
+    pub fn new(max: Duration) -> Self {
+        Self {
+            max,
+            best_cost: f64::INFINITY,
+            best_time: Duration::ZERO,
+        }
+    }
+}
+
+impl<I: State> TerminationCriterion<I> for StallTime {
+    fn check(&mut self, state: &I) -> Status {
+        let time = state.get_time().unwrap_or_default();
+        if state.get_best_cost() < self.best_cost {
+            self.best_cost = state.get_best_cost();
+            self.best_time = time;
+        } else if self.max <= time.saturating_sub(self.best_time) {
+            return Status::Terminated(Reason::MaxStallTimeReached);
+        }
+        Status::NotTerminated
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TargetCost(pub f64);
+
+impl<I: State> TerminationCriterion<I> for TargetCost {
+    fn check(&mut self, state: &I) -> Status {
+        if state.get_best_cost() <= self.0 {
+            return Status::Terminated(Reason::TargetCostReached);
+        }
+        Status::NotTerminated
+    }
+}
+
+/// Relative decrease of the best cost over the last `window` checks below `epsilon`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelativeImprovement {
+    window: usize,
+    epsilon: f64,
+    best_costs: VecDeque<f64>,
+}
+
+impl RelativeImprovement {
+    pub fn new(window: usize, epsilon: f64) -> Self {
+        Self {
+            window,
+            epsilon,
+            best_costs: VecDeque::with_capacity(window + 1),
+        }
+    }
+}
+
+impl<I: State> TerminationCriterion<I> for RelativeImprovement {
+    fn check(&mut self, state: &I) -> Status {
+        let best_cost = state.get_best_cost();
+        self.best_costs.push_back(best_cost);
+        if self.best_costs.len() <= self.window {
+            return Status::NotTerminated;
+        }
+        let old = self.best_costs.pop_front().unwrap();
+        if !old.is_finite() {
+            return Status::NotTerminated;
+        }
+        if (old - best_cost) / old.abs().max(f64::EPSILON) < self.epsilon {
+            return Status::Terminated(Reason::InsufficientImprovement);
+        }
+        Status::NotTerminated
+    }
+}
+
+/// Number of cost function evaluations, shared between its clones and threads. Full
+/// evaluations and delta evaluations are counted apart, a delta evaluation weighing
+/// `delta_weight` of a full one, e.g. `1.0 / n` when it only looks at 1 of n elements.
+/// The cost function counts its own evaluations, or is wrapped in `crate::Counting`.
+#[derive(Clone, Debug)]
+pub struct EvaluationCounter {
+    costs: Arc<AtomicU64>,
+    deltas: Arc<AtomicU64>,
+    /// Bits of the `f64` weight, shared like the counts.
+    delta_weight: Arc<AtomicU64>,
+}
+
+impl Default for EvaluationCounter {
+    fn default() -> Self {
+        Self {
+            costs: Arc::default(),
+            deltas: Arc::default(),
+            delta_weight: Arc::new(AtomicU64::new(1.0_f64.to_bits())),
+        }
+    }
+}
+
+impl EvaluationCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like the counts, the weight is shared with every clone of the counter, including
+    /// the ones held by `MaxEvaluations` budgets.
+    pub fn set_delta_weight(&self, delta_weight: f64) {
+        self.delta_weight
+            .store(delta_weight.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Count a full evaluation.
+    pub fn increment(&self) {
+        self.costs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a delta evaluation.
+    pub fn increment_delta(&self) {
+        self.deltas.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn costs(&self) -> u64 {
+        self.costs.load(Ordering::Relaxed)
+    }
+
+    pub fn deltas(&self) -> u64 {
+        self.deltas.load(Ordering::Relaxed)
+    }
+
+    pub fn delta_weight(&self) -> f64 {
+        f64::from_bits(self.delta_weight.load(Ordering::Relaxed))
+    }
+
+    /// Weighted number of evaluations.
+    pub fn evaluations(&self) -> f64 {
+        self.costs() as f64 + self.delta_weight() * self.deltas() as f64
+    }
+}
+
+/// Weighted number of evaluations, see `EvaluationCounter`.
+#[derive(Clone, Debug)]
+pub struct MaxEvaluations {
+    counter: EvaluationCounter,
+    max: f64,
+}
+
+impl MaxEvaluations {
+    pub fn new(counter: EvaluationCounter, max: f64) -> Self {
+        Self { counter, max }
+    }
+}
+
+impl<I> TerminationCriterion<I> for MaxEvaluations {
+    fn check(&mut self, _state: &I) -> Status {
+        if self.max <= self.counter.evaluations() {
+            return Status::Terminated(Reason::MaxEvaluationsReached);
+        }
+        Status::NotTerminated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EvaluationCounter, MaxEvaluations, MaxIters, Reason, RelativeImprovement, StallIters,
+        Status, TargetCost, TerminationCriterion,
+    };
+    use crate::{IterState, State};
+
+    fn state(iter: u32, best_cost: f64) -> IterState<()> {
+        let mut state = IterState::new();
+        state.iter = iter;
+        state.best_cost = best_cost;
+        state
+    }
+
+    #[test]
+    fn test_combinators() {
+        let mut any = MaxIters(10).or(TargetCost(0.0));
+        assert!(!any.check(&state(5, 1.0)).terminated());
+        assert_eq!(
+            any.check(&state(5, 0.0)),
+            Status::Terminated(Reason::TargetCostReached)
+        );
+
+        let mut all = MaxIters(10).and(StallIters::new(3));
+        assert!(!all.check(&state(10, 2.0)).terminated());
+        // Three iterations without a new best are allowed
+        for _ in 0..3 {
+            assert!(!all.check(&state(10, 2.0)).terminated());
+        }
+        assert_eq!(
+            all.check(&state(10, 2.0)),
+            Status::Terminated(Reason::MaxItersReached)
+        );
+
+        let mut custom = |state: &IterState<()>| {
+            if state.get_cost() < 0.0 {
+                Status::Terminated(Reason::SolverExit("Negative cost".to_owned()))
+            } else {
+                Status::NotTerminated
+            }
+        };
+        assert!(!custom.check(&state(0, 1.0)).terminated());
+    }
+
+    #[test]
+    fn test_relative_improvement() {
+        let mut criterion = RelativeImprovement::new(2, 0.1);
+        assert!(!criterion.check(&state(0, 10.0)).terminated());
+        assert!(!criterion.check(&state(1, 9.0)).terminated());
+        // 10 to 8.5 over the window
+        assert!(!criterion.check(&state(2, 8.5)).terminated());
+        // 9 to 8.5 over the window
+        assert!(criterion.check(&state(3, 8.5)).terminated());
+    }
+
+    #[test]
+    fn test_max_evaluations() {
+        let counter = EvaluationCounter::new();
+        counter.set_delta_weight(0.25);
+        let mut criterion = MaxEvaluations::new(counter.clone(), 2.0);
+        counter.increment();
+        counter.increment_delta();
+        assert!(!TerminationCriterion::<()>::check(&mut criterion, &()).terminated());
+        for _ in 0..3 {
+            counter.increment_delta();
+        }
+        assert_eq!((counter.costs(), counter.deltas()), (1, 4));
+        assert!(TerminationCriterion::<()>::check(&mut criterion, &()).terminated());
+    }
+
+    #[test]
+    fn test_shared_delta_weight() {
+        let counter = EvaluationCounter::new();
+        let mut criterion = MaxEvaluations::new(counter.clone(), 2.0);
+        counter.set_delta_weight(0.5);
+        for _ in 0..3 {
+            counter.increment_delta();
+        }
+        assert_eq!(counter.evaluations(), 1.5);
+        assert!(!TerminationCriterion::<()>::check(&mut criterion, &()).terminated());
+        counter.increment_delta();
+        assert!(TerminationCriterion::<()>::check(&mut criterion, &()).terminated());
+    }
+}
@@ -0,0 +1,523 @@
+//! Automatic tuning of solver hyperparameters by racing (F-race): candidate configurations
+//! drawn from a `ParameterSpace` are evaluated block after block, a block being a training
+//! instance with a seed, and the configurations that are significantly worse according to
+//! a Friedman test are discarded as soon as possible.
+use crate::errors::LocalSearchError;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+/// Values a hyperparameter can take.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Domain {
+    Categorical(Vec<String>),
+    /// Inclusive bounds.
+    Integer(i64, i64),
+    Real(f64, f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Categorical(String),
+    Integer(i64),
+    Real(f64),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Categorical(value) => write!(f, "{value}"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Real(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Named hyperparameters with their domains, e.g. the tabu list capacity.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterSpace {
+    params: Vec<(String, Domain)>,
+}
+
+impl ParameterSpace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn categorical(mut self, name: &str, values: &[&str]) -> Self {
+        let values = values.iter().map(|value| (*value).to_owned()).collect();
+        self.params
+            .push((name.to_owned(), Domain::Categorical(values)));
+        self
+    }
+
+    #[must_use]
+    pub fn integer(mut self, name: &str, lower: i64, upper: i64) -> Self {
+        self.params
+            .push((name.to_owned(), Domain::Integer(lower, upper)));
+        self
+    }
+
+    #[must_use]
+    pub fn real(mut self, name: &str, lower: f64, upper: f64) -> Self {
+        self.params
+            .push((name.to_owned(), Domain::Real(lower, upper)));
+        self
+    }
+
+    pub fn params(&self) -> &[(String, Domain)] {
+        &self.params
+    }
+
+    /// Draw every hyperparameter uniformly in its domain.
+    ///
+    /// # Panics
+    ///
+    /// Panic if a categorical domain is empty or bounds are reversed.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Configuration {
+        let values = self
+            .params
+            .iter()
+            .map(|(name, domain)| {
+                let value = match domain {
+                    Domain::Categorical(values) => {
+                        Value::Categorical(values.choose(rng).unwrap().clone())
+                    }
+                    Domain::Integer(lower, upper) => Value::Integer(rng.gen_range(*lower..=*upper)),
+                    Domain::Real(lower, upper) => Value::Real(rng.gen_range(*lower..=*upper)),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        Configuration { values }
+    }
+}
+
+/// A value for each hyperparameter of a `ParameterSpace`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Configuration {
+    values: Vec<(String, Value)>,
+}
+
+impl Configuration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `name` to `value`, e.g. to race hand-tuned configurations.
+    #[must_use]
+    pub fn with(mut self, name: &str, value: Value) -> Self {
+        match self.values.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.values.push((name.to_owned(), value)),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    pub fn categorical(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            Value::Categorical(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            Value::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn real(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            Value::Real(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Configuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values: Vec<String> = self
+            .values
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        write!(f, "{}", values.join(" "))
+    }
+}
+
+/// Outcome of a configuration in the race.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CandidateReport {
+    pub configuration: Configuration,
+    /// Cost of each block the configuration was evaluated on.
+    pub costs: Vec<f64>,
+    /// Number of blocks evaluated when it was discarded, `None` if it survived.
+    pub eliminated_at: Option<usize>,
+}
+
+impl CandidateReport {
+    pub fn mean_cost(&self) -> f64 {
+        self.costs.iter().sum::<f64>() / self.costs.len().max(1) as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuningReport {
+    pub best: Configuration,
+    pub candidates: Vec<CandidateReport>,
+    pub blocks: usize,
+    pub experiments: usize,
+}
+
+impl TuningReport {
+    pub fn survivors(&self) -> impl Iterator<Item = &CandidateReport> {
+        self.candidates
+            .iter()
+            .filter(|candidate| candidate.eliminated_at.is_none())
+    }
+}
+
+/// F-race over the blocks made of every training instance with every seed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Race {
+    num_configurations: usize,
+    configurations: Vec<Configuration>,
+    first_test: usize,
+    confidence: f64,
+    max_experiments: usize,
+}
+
+impl Race {
+    /// Race `num_configurations` configurations drawn at random.
+    pub fn new(num_configurations: usize) -> Self {
+        Self {
+            num_configurations,
+            configurations: Vec::new(),
+            first_test: 5,
+            confidence: 0.95,
+            max_experiments: usize::MAX,
+        }
+    }
+
+    /// Also race the given configurations, e.g. the current defaults.
+    #[must_use]
+    pub fn with_configurations(mut self, configurations: Vec<Configuration>) -> Self {
+        self.configurations = configurations;
+        self
+    }
+
+    /// Number of blocks evaluated before the first test, at least 2.
+    #[must_use]
+    pub fn with_first_test(mut self, first_test: usize) -> Self {
+        self.first_test = first_test.max(2);
+        self
+    }
+
+    /// Confidence level of the tests.
+    #[must_use]
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// Stop before running more than `max_experiments` evaluations.
+    #[must_use]
+    pub fn with_max_experiments(mut self, max_experiments: usize) -> Self {
+        self.max_experiments = max_experiments;
+        self
+    }
+
+    /// `evaluate` runs a configuration on an instance with a seed and returns the cost
+    /// reached, the lower the better. The best configuration is the survivor with the
+    /// lowest sum of ranks.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an evaluation fails, or `LocalSearchError::NotInitialized` if
+    /// there is no configuration, instance or seed.
+    pub fn run<I, F, R>(
+        &self,
+        space: &ParameterSpace,
+        instances: &[I],
+        seeds: &[u64],
+        mut evaluate: F,
+        rng: &mut R,
+    ) -> Result<TuningReport, LocalSearchError>
+    where
+        F: FnMut(&Configuration, &I, u64) -> Result<f64, LocalSearchError>,
+        R: Rng,
+    {
+        let mut candidates: Vec<CandidateReport> = self
+            .configurations
+            .iter()
+            .cloned()
+            .chain((0..self.num_configurations).map(|_| space.sample(rng)))
+            .map(|configuration| CandidateReport {
+                configuration,
+                costs: Vec::new(),
+                eliminated_at: None,
+            })
+            .collect();
+        if candidates.is_empty() || instances.is_empty() || seeds.is_empty() {
+            return Err(LocalSearchError::NotInitialized);
+        }
+
+        let mut blocks = 0;
+        let mut experiments = 0;
+        for &seed in seeds {
+            for instance in instances {
+                let alive: Vec<usize> = (0..candidates.len())
+                    .filter(|&i| candidates[i].eliminated_at.is_none())
+                    .collect();
+                if alive.len() < 2 || experiments + alive.len() > self.max_experiments {
+                    return Ok(Self::report(candidates, blocks, experiments));
+                }
+                for &i in &alive {
+                    let cost = evaluate(&candidates[i].configuration, instance, seed)?;
+                    candidates[i].costs.push(cost);
+                }
+                blocks += 1;
+                experiments += alive.len();
+                if blocks >= self.first_test {
+                    let costs: Vec<&[f64]> = alive
+                        .iter()
+                        .map(|&i| candidates[i].costs.as_slice())
+                        .collect();
+                    for j in self.friedman_test(&costs) {
+                        candidates[alive[j]].eliminated_at = Some(blocks);
+                    }
+                }
+            }
+        }
+        Ok(Self::report(candidates, blocks, experiments))
+    }
+
+    fn report(candidates: Vec<CandidateReport>, blocks: usize, experiments: usize) -> TuningReport {
+        let survivors: Vec<&CandidateReport> = candidates
+            .iter()
+            .filter(|candidate| candidate.eliminated_at.is_none())
+            .collect();
+        let costs: Vec<&[f64]> = survivors.iter().map(|c| c.costs.as_slice()).collect();
+        let rank_sums = rank_sums(&costs, blocks);
+        let best = (0..survivors.len())
+            .min_by(|&a, &b| {
+                rank_sums[a].total_cmp(&rank_sums[b]).then(
+                    survivors[a]
+                        .mean_cost()
+                        .total_cmp(&survivors[b].mean_cost()),
+                )
+            })
+            .map(|i| survivors[i].configuration.clone())
+            .unwrap();
+        TuningReport {
+            best,
+            candidates,
+            blocks,
+            experiments,
+        }
+    }
+
+    /// Indices of the candidates significantly worse than the best one, given their costs
+    /// on every block.
+    fn friedman_test(&self, costs: &[&[f64]]) -> Vec<usize> {
+        let k = costs.len() as f64;
+        let n = costs[0].len();
+        let ranks: Vec<Vec<f64>> = (0..n)
+            .map(|l| ranks(&costs.iter().map(|c| c[l]).collect::<Vec<f64>>()))
+            .collect();
+        let rank_sums: Vec<f64> = (0..costs.len())
+            .map(|j| ranks.iter().map(|r| r[j]).sum())
+            .collect();
+        let n = n as f64;
+        let squares: f64 = ranks.iter().flatten().map(|r| r * r).sum();
+        let correction = n * k * (k + 1.0).powi(2) / 4.0;
+        let spread = squares - correction;
+        if spread <= f64::EPSILON {
+            return Vec::new();
+        }
+        let statistic = (k - 1.0)
+            * rank_sums
+                .iter()
+                .map(|r| (r - n * (k + 1.0) / 2.0).powi(2))
+                .sum::<f64>()
+            / spread;
+        if statistic <= chi_squared_quantile(self.confidence, k - 1.0) {
+            return Vec::new();
+        }
+        // Post-hoc comparison of every candidate with the best one
+        let df = (n - 1.0) * (k - 1.0);
+        let difference = t_quantile(1.0 - (1.0 - self.confidence) / 2.0, df)
+            * (2.0 * n * (1.0 - statistic / (n * (k - 1.0))) * spread / df)
+                .max(0.0)
+                .sqrt();
+        let best = rank_sums.iter().copied().fold(f64::INFINITY, f64::min);
+        (0..rank_sums.len())
+            .filter(|&j| rank_sums[j] - best > difference)
+            .collect()
+    }
+}
+
+/// Ranks of `values` from 1, ties sharing their average rank.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Sum over the first `blocks` blocks of the ranks of each candidate.
+fn rank_sums(costs: &[&[f64]], blocks: usize) -> Vec<f64> {
+    let mut sums = vec![0.0; costs.len()];
+    for l in 0..blocks {
+        let block: Vec<f64> = costs.iter().map(|c| c[l]).collect();
+        for (sum, rank) in sums.iter_mut().zip(ranks(&block)) {
+            *sum += rank;
+        }
+    }
+    sums
+}
+
+/// Quantile of the standard normal distribution, by Acklam's rational approximation.
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    let low = 0.02425;
+    if p < low {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p <= 1.0 - low {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    }
+}
+
+/// Quantile of the chi-squared distribution, by the Wilson-Hilferty approximation.
+fn chi_squared_quantile(p: f64, df: f64) -> f64 {
+    let z = normal_quantile(p);
+    let a = 2.0 / (9.0 * df);
+    df * (1.0 - a + z * a.sqrt()).powi(3).max(0.0)
+}
+
+/// Quantile of the Student t distribution, by its Cornish-Fisher expansion.
+fn t_quantile(p: f64, df: f64) -> f64 {
+    let z = normal_quantile(p);
+    let z2 = z * z;
+    let g1 = (z2 + 1.0) * z / 4.0;
+    let g2 = ((5.0 * z2 + 16.0) * z2 + 3.0) * z / 96.0;
+    let g3 = (((3.0 * z2 + 19.0) * z2 + 17.0) * z2 - 15.0) * z / 384.0;
+    let g4 = ((((79.0 * z2 + 776.0) * z2 + 1482.0) * z2 - 1920.0) * z2 - 945.0) * z / 92160.0;
+    z + g1 / df + g2 / df.powi(2) + g3 / df.powi(3) + g4 / df.powi(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        chi_squared_quantile, normal_quantile, ranks, t_quantile, Configuration, ParameterSpace,
+        Race, Value,
+    };
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_quantiles() {
+        assert!((normal_quantile(0.975) - 1.959_964).abs() < 1e-6);
+        assert!((normal_quantile(0.01) + 2.326_348).abs() < 1e-6);
+        assert!((chi_squared_quantile(0.95, 3.0) - 7.815).abs() < 0.05);
+        assert!((chi_squared_quantile(0.95, 10.0) - 18.307).abs() < 0.05);
+        assert!((t_quantile(0.975, 10.0) - 2.228).abs() < 0.005);
+        assert!((t_quantile(0.975, 30.0) - 2.042).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_ranks() {
+        assert_eq!(ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+
+    #[test]
+    fn test_race() {
+        let space = ParameterSpace::new()
+            .integer("x", 0, 10)
+            .categorical("sign", &["plus", "minus"]);
+        let default = Configuration::new()
+            .with("x", Value::Integer(3))
+            .with("sign", Value::Categorical("plus".to_owned()));
+        let mut rng = StdRng::seed_from_u64(0);
+        let report = Race::new(20)
+            .with_configurations(vec![default.clone()])
+            .run(
+                &space,
+                &[0.0, 1.0, 2.0],
+                &(0..10).collect::<Vec<u64>>(),
+                |configuration, instance, seed| {
+                    let x = configuration.integer("x").unwrap() as f64;
+                    let sign = match configuration.categorical("sign").unwrap() {
+                        "plus" => 0.0,
+                        _ => 5.0,
+                    };
+                    let noise = StdRng::seed_from_u64(seed).gen_range(0.0..0.5);
+                    Ok((x - 3.0).powi(2) + sign + instance + noise)
+                },
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(report.best, default);
+        assert_eq!(report.candidates.len(), 21);
+        assert!(report.experiments < 21 * 30);
+        assert!(report
+            .survivors()
+            .all(|c| c.configuration.integer("x") == Some(3)));
+    }
+}
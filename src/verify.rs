@@ -0,0 +1,224 @@
+//! Consistency checks of user problem implementations.
+//!
+//! `TabuSearch` and `VariableNeighborhood` track the current cost as the previous cost
+//! plus the delta of the applied move, so a `Neighborhood::get_neighbor_delta` that
+//! disagrees with `CostFunction::cost` silently corrupts the costs reported in the state.
+use crate::errors::LocalSearchError;
+use crate::problem::{CostFunction, Neighborhood};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum InconsistencyKind {
+    /// The delta disagrees with `cost(make_move(param, neighbor)) - cost(param)`.
+    Delta { reported: f64, expected: f64 },
+    /// `get_neighbor_delta` or `make_move` modified the param they were given.
+    MutatedParam,
+    /// `apply_move` disagrees with `make_move`.
+    ApplyMove,
+    /// `undo_move` does not restore the param. Not reported when it is unsupported.
+    UndoMove,
+    /// `get_neighbor_delta` or `make_move` returned an error.
+    Failed(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inconsistency<N> {
+    pub neighbor: N,
+    pub kind: InconsistencyKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckReport<N> {
+    pub checked: usize,
+    pub inconsistencies: Vec<Inconsistency<N>>,
+}
+
+impl<N> CheckReport<N> {
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+}
+
+impl<N: fmt::Debug> fmt::Display for CheckReport<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} sampled neighbors are inconsistent",
+            self.inconsistencies.len(),
+            self.checked
+        )?;
+        for Inconsistency { neighbor, kind } in &self.inconsistencies {
+            match kind {
+                InconsistencyKind::Delta { reported, expected } => write!(
+                    f,
+                    "\n    {neighbor:?}: delta {reported} but cost difference {expected}"
+                )?,
+                InconsistencyKind::MutatedParam => write!(f, "\n    {neighbor:?}: param mutated")?,
+                InconsistencyKind::ApplyMove => {
+                    write!(f, "\n    {neighbor:?}: apply_move disagrees with make_move")?;
+                }
+                InconsistencyKind::UndoMove => {
+                    write!(
+                        f,
+                        "\n    {neighbor:?}: undo_move does not restore the param"
+                    )?;
+                }
+                InconsistencyKind::Failed(err) => write!(f, "\n    {neighbor:?}: {err}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Samples neighbors of a param and compares their delta against a full evaluation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NeighborhoodChecker {
+    samples: usize,
+    tolerance: f64,
+}
+
+impl Default for NeighborhoodChecker {
+    fn default() -> Self {
+        Self {
+            samples: 100,
+            tolerance: 1e-9,
+        }
+    }
+}
+
+impl NeighborhoodChecker {
+    pub fn new(samples: usize, tolerance: f64) -> Self {
+        Self { samples, tolerance }
+    }
+
+    /// # Errors
+    ///
+    /// Will return `Err` if the neighbors cannot be generated or `param` cannot be
+    /// evaluated.
+    pub fn check<O, P, N, R>(
+        &self,
+        problem: &O,
+        param: &P,
+        rng: &mut R,
+    ) -> Result<CheckReport<N>, LocalSearchError>
+    where
+        O: CostFunction<Param = P> + Neighborhood<Param = P, Neighbor = N>,
+        P: Clone + PartialEq,
+        N: Clone,
+        R: Rng,
+    {
+        let original = param.clone();
+        let cost = problem.cost(param)?;
+        let neighbors = problem.get_neighbor_moves(rng, param)?;
+        let mut report = CheckReport {
+            checked: 0,
+            inconsistencies: Vec::new(),
+        };
+        for neighbor in neighbors.choose_multiple(rng, self.samples) {
+            report.checked += 1;
+            let result = problem
+                .get_neighbor_delta(param, neighbor)
+                .and_then(|delta| {
+                    let new_param = problem.make_move(param, neighbor)?;
+                    let mut applied = param.clone();
+                    problem.apply_move(&mut applied, neighbor)?;
+                    let applied_ok = applied == new_param;
+                    let undo_ok = match problem.undo_move(&mut applied, neighbor) {
+                        Err(LocalSearchError::FailUndoMove) => true,
+                        result => result.is_ok() && applied == original,
+                    };
+                    Ok((delta, problem.cost(&new_param)? - cost, applied_ok, undo_ok))
+                });
+            let kind = match result {
+                Err(err) => Some(InconsistencyKind::Failed(err.to_string())),
+                Ok(_) if *param != original => Some(InconsistencyKind::MutatedParam),
+                Ok((_, _, false, _)) => Some(InconsistencyKind::ApplyMove),
+                Ok((_, _, _, false)) => Some(InconsistencyKind::UndoMove),
+                Ok((reported, expected, ..)) if (reported - expected).abs() > self.tolerance => {
+                    Some(InconsistencyKind::Delta { reported, expected })
+                }
+                Ok(_) => None,
+            };
+            if let Some(kind) = kind {
+                report.inconsistencies.push(Inconsistency {
+                    neighbor: neighbor.clone(),
+                    kind,
+                });
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InconsistencyKind, NeighborhoodChecker};
+    use crate::errors::LocalSearchError;
+    use crate::problem::{CostFunction, Neighborhood};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Sum of the values, with a delta forgetting the old value of index 0.
+    struct Sum;
+
+    impl CostFunction for Sum {
+        type Param = Vec<i64>;
+
+        fn cost(&self, param: &Vec<i64>) -> Result<f64, LocalSearchError> {
+            Ok(param.iter().sum::<i64>() as f64)
+        }
+    }
+
+    impl Neighborhood for Sum {
+        type Param = Vec<i64>;
+        type Neighbor = (usize, i64);
+
+        fn get_neighbor_moves<R: Rng>(
+            &self,
+            _rng: &mut R,
+            param: &Vec<i64>,
+        ) -> Result<Vec<(usize, i64)>, LocalSearchError> {
+            Ok((0..param.len()).map(|i| (i, 5)).collect())
+        }
+
+        fn get_neighbor_delta(
+            &self,
+            param: &Vec<i64>,
+            neighbor: &(usize, i64),
+        ) -> Result<f64, LocalSearchError> {
+            let (i, value) = *neighbor;
+            Ok(if i == 0 { value } else { value - param[i] } as f64)
+        }
+
+        fn make_move(
+            &self,
+            param: &Vec<i64>,
+            neighbor: &(usize, i64),
+        ) -> Result<Vec<i64>, LocalSearchError> {
+            let mut new_param = param.clone();
+            new_param[neighbor.0] = neighbor.1;
+            Ok(new_param)
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let report = NeighborhoodChecker::new(10, 1e-9)
+            .check(&Sum, &vec![1, 2, 3], &mut rng)
+            .unwrap();
+        assert_eq!(report.checked, 3);
+        assert!(!report.is_consistent());
+        assert_eq!(report.inconsistencies.len(), 1);
+        assert_eq!(report.inconsistencies[0].neighbor, (0, 5));
+        assert_eq!(
+            report.inconsistencies[0].kind,
+            InconsistencyKind::Delta {
+                reported: 5.0,
+                expected: 4.0
+            }
+        );
+    }
+}
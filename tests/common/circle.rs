@@ -0,0 +1,145 @@
+use localsearch_rs::{
+    AugmentedNeighborhood, CostFunction, LocalSearchError, Neighborhood, Penalty,
+};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::f64::consts::PI;
+
+pub const NUM_CITIES: usize = 12;
+
+pub type Edge = (usize, usize);
+
+/// Tour of cities evenly spaced on the unit circle. A neighbor `(a, b)` reverses the
+/// segment from city `a` to city `b`, i.e. a 2-opt move.
+pub struct Circle;
+
+impl Circle {
+    fn distance(a: usize, b: usize) -> f64 {
+        let angle = |city: usize| 2.0 * PI * city as f64 / NUM_CITIES as f64;
+        let (a, b) = (angle(a), angle(b));
+        (b.cos() - a.cos()).hypot(b.sin() - a.sin())
+    }
+
+    fn edge(a: usize, b: usize) -> Edge {
+        (a.min(b), a.max(b))
+    }
+
+    pub fn optimal_length() -> f64 {
+        NUM_CITIES as f64 * Self::distance(0, 1)
+    }
+
+    /// Removed and added edges of a 2-opt move.
+    fn exchanged_edges(param: &[usize], &(a, b): &(usize, usize)) -> ([Edge; 2], [Edge; 2]) {
+        let n = param.len();
+        let i = param.iter().position(|&c| c == a).unwrap();
+        let j = param.iter().position(|&c| c == b).unwrap();
+        let (i, j) = (i.min(j), i.max(j));
+        let (prev, next) = (param[(i + n - 1) % n], param[(j + 1) % n]);
+        (
+            [Self::edge(prev, param[i]), Self::edge(param[j], next)],
+            [Self::edge(prev, param[j]), Self::edge(param[i], next)],
+        )
+    }
+}
+
+impl CostFunction for Circle {
+    type Param = Vec<usize>;
+
+    fn cost(&self, param: &Vec<usize>) -> Result<f64, LocalSearchError> {
+        Ok((0..param.len())
+            .map(|i| Self::distance(param[i], param[(i + 1) % param.len()]))
+            .sum())
+    }
+}
+
+impl Neighborhood for Circle {
+    type Param = Vec<usize>;
+    type Neighbor = (usize, usize);
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Vec<usize>,
+    ) -> Result<Vec<(usize, usize)>, LocalSearchError> {
+        let n = param.len();
+        let mut moves: Vec<(usize, usize)> = (1..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .map(|(i, j)| (param[i], param[j]))
+            .collect();
+        moves.shuffle(rng);
+        Ok(moves)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Vec<usize>,
+        neighbor: &(usize, usize),
+    ) -> Result<f64, LocalSearchError> {
+        let (removed, added) = Self::exchanged_edges(param, neighbor);
+        let length = |edges: [Edge; 2]| {
+            edges
+                .iter()
+                .map(|&(a, b)| Self::distance(a, b))
+                .sum::<f64>()
+        };
+        Ok(length(added) - length(removed))
+    }
+
+    fn make_move(
+        &self,
+        param: &Vec<usize>,
+        &(a, b): &(usize, usize),
+    ) -> Result<Vec<usize>, LocalSearchError> {
+        let i = param.iter().position(|&c| c == a).unwrap();
+        let j = param.iter().position(|&c| c == b).unwrap();
+        let mut new_param = param.clone();
+        new_param[i.min(j)..=i.max(j)].reverse();
+        Ok(new_param)
+    }
+
+    fn move_anchors(&self, &(a, b): &(usize, usize)) -> Vec<usize> {
+        vec![a, b]
+    }
+}
+
+impl AugmentedNeighborhood<Edge> for Circle {
+    type Param = Vec<usize>;
+    type Neighbor = (usize, usize);
+    type Penalty = Penalty<Edge>;
+
+    fn get_neighbor_augmented_delta(
+        &self,
+        param: &Vec<usize>,
+        neighbor: &(usize, usize),
+        penalty: &Penalty<Edge>,
+    ) -> Result<f64, LocalSearchError> {
+        let (removed, added) = Self::exchanged_edges(param, neighbor);
+        let penalties = |edges: [Edge; 2]| edges.iter().map(|edge| penalty.get(edge)).sum::<f64>();
+        Ok(self.get_neighbor_delta(param, neighbor)?
+            + penalty.lambda * (penalties(added) - penalties(removed)))
+    }
+
+    fn features(&self, param: &Vec<usize>) -> Result<Vec<(Edge, f64)>, LocalSearchError> {
+        Ok((0..param.len())
+            .map(|i| {
+                let (a, b) = (param[i], param[(i + 1) % param.len()]);
+                (Self::edge(a, b), Self::distance(a, b))
+            })
+            .collect())
+    }
+
+    fn feature_anchors(&self, &(a, b): &Edge) -> Vec<usize> {
+        vec![a, b]
+    }
+
+    fn number_of_features(&self, param: &Vec<usize>) -> Result<u32, LocalSearchError> {
+        Ok(param.len() as u32)
+    }
+}
+
+pub fn init_solution(rng: &mut StdRng) -> Vec<usize> {
+    let mut param: Vec<usize> = (0..NUM_CITIES).collect();
+    param.shuffle(rng);
+    param
+}
@@ -1,7 +1,18 @@
-use localsearch_rs::{CostFunction, LocalSearchError, Neighborhood};
+#![allow(
+    dead_code,
+    clippy::clone_on_copy,
+    clippy::needless_range_loop,
+    clippy::useless_conversion
+)]
+
+use localsearch_rs::{
+    Constructive, CostFunction, IncrementalEvaluation, LocalSearchError, Neighborhood,
+};
 use rand::seq::SliceRandom;
 use rand::Rng;
 
+pub mod circle;
+
 const BOARD_SIZE: usize = 8;
 
 type ChessBoard = [[bool; BOARD_SIZE]; BOARD_SIZE];
@@ -24,6 +35,10 @@ impl Neighborhood for EightQueens {
     type Param = ChessBoard;
     type Neighbor = (usize, usize);
 
+    fn random_param<R: Rng>(&self, rng: &mut R) -> Result<ChessBoard, LocalSearchError> {
+        Self::init_solution(rng)
+    }
+
     fn get_neighbor_moves<R: Rng>(
         &self,
         rng: &mut R,
@@ -58,6 +73,10 @@ impl Neighborhood for EightQueens {
         new_state[*queen_on_row][*queen_new_col] = true;
         Ok(new_state)
     }
+
+    fn move_anchors(&self, neighbor: &Self::Neighbor) -> Vec<usize> {
+        vec![neighbor.0]
+    }
 }
 
 impl CostFunction for EightQueens {
@@ -103,3 +122,109 @@ impl CostFunction for EightQueens {
         Ok(num_attacking as f64)
     }
 }
+
+/// Number of queens on each column and diagonal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueenLines {
+    cols: [i64; BOARD_SIZE],
+    diags: [i64; 2 * BOARD_SIZE - 1],
+    anti_diags: [i64; 2 * BOARD_SIZE - 1],
+}
+
+impl QueenLines {
+    fn lines(row: usize, col: usize) -> (usize, usize, usize) {
+        (col, row + BOARD_SIZE - 1 - col, row + col)
+    }
+
+    fn add(&mut self, row: usize, col: usize, count: i64) {
+        let (col, diag, anti_diag) = Self::lines(row, col);
+        self.cols[col] += count;
+        self.diags[diag] += count;
+        self.anti_diags[anti_diag] += count;
+    }
+}
+
+fn queen_col(param: &ChessBoard, row: usize) -> usize {
+    param[row].iter().position(|&queen| queen).unwrap()
+}
+
+/// Every pair of queens on a line attack each other twice in `EightQueens::cost`.
+impl IncrementalEvaluation for EightQueens {
+    type Param = ChessBoard;
+    type Neighbor = (usize, usize);
+    type Cache = QueenLines;
+
+    fn init_cache(&self, param: &ChessBoard) -> Result<QueenLines, LocalSearchError> {
+        let mut lines = QueenLines {
+            cols: [0; BOARD_SIZE],
+            diags: [0; 2 * BOARD_SIZE - 1],
+            anti_diags: [0; 2 * BOARD_SIZE - 1],
+        };
+        for row in 0..BOARD_SIZE {
+            lines.add(row, queen_col(param, row), 1);
+        }
+        Ok(lines)
+    }
+
+    fn get_cached_delta(
+        &self,
+        param: &ChessBoard,
+        lines: &QueenLines,
+        &(row, new_col): &(usize, usize),
+    ) -> Result<f64, LocalSearchError> {
+        let old_col = queen_col(param, row);
+        if old_col == new_col {
+            return Ok(0.0);
+        }
+        let (c0, d0, a0) = QueenLines::lines(row, old_col);
+        let (c1, d1, a1) = QueenLines::lines(row, new_col);
+        let removed = lines.cols[c0] + lines.diags[d0] + lines.anti_diags[a0] - 3;
+        let added = lines.cols[c1] + lines.diags[d1] + lines.anti_diags[a1];
+        Ok((2 * (added - removed)) as f64)
+    }
+
+    fn update_cache(
+        &self,
+        param: &ChessBoard,
+        lines: &mut QueenLines,
+        &(row, new_col): &(usize, usize),
+    ) -> Result<(), LocalSearchError> {
+        lines.add(row, queen_col(param, row), -1);
+        lines.add(row, new_col, 1);
+        Ok(())
+    }
+}
+
+/// Queens are placed row by row, the elements being the columns of the next queen.
+impl Constructive for EightQueens {
+    type Param = ChessBoard;
+    type Element = usize;
+
+    fn empty_solution(&self) -> Result<ChessBoard, LocalSearchError> {
+        Ok([[false; BOARD_SIZE]; BOARD_SIZE])
+    }
+
+    fn candidates(&self, partial: &ChessBoard) -> Result<Vec<usize>, LocalSearchError> {
+        if partial.iter().all(|row| row.contains(&true)) {
+            return Ok(Vec::new());
+        }
+        Ok((0..BOARD_SIZE).collect())
+    }
+
+    fn greedy_cost(&self, partial: &ChessBoard, &col: &usize) -> Result<f64, LocalSearchError> {
+        let row = partial.iter().take_while(|row| row.contains(&true)).count();
+        let attacks = (0..row)
+            .filter(|&other| {
+                let other_col = queen_col(partial, other);
+                other_col == col || other_col.abs_diff(col) == row - other
+            })
+            .count();
+        Ok((2 * attacks) as f64)
+    }
+
+    fn add_element(&self, partial: &mut ChessBoard, &col: &usize) -> Result<(), LocalSearchError> {
+        let row = partial.iter().take_while(|row| row.contains(&true)).count();
+        partial[row][col] = true;
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use localsearch_rs::problems::knapsack::Knapsack;
+use localsearch_rs::{CostFunction, Executor, Penalized, PenaltyAdaptation, TabuSearch};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const INIT_WEIGHT: f64 = 0.1;
+
+#[test]
+fn test_penalized() {
+    // The capacity is declared as a constraint instead of being penalized by the cost
+    let knapsack = Knapsack::single(vec![10.0, 7.0, 5.0, 3.0], vec![5.0, 4.0, 3.0, 2.0], 9.0)
+        .with_penalty(0.0);
+    let param = knapsack.solution(vec![false; 4]).unwrap();
+    let all = knapsack.solution(vec![true; 4]).unwrap();
+    // Taking every item pays off at this weight, until it grows. The weight is not
+    // decreased once feasible, so that the search settles on feasible params
+    let adaptation = PenaltyAdaptation {
+        decrease: 1.0,
+        ..PenaltyAdaptation::default()
+    };
+    let problem = Penalized::new(knapsack, INIT_WEIGHT).with_adaptation(Some(adaptation));
+    assert!(problem.cost(&all).unwrap() < -17.0);
+    let solver = TabuSearch::new(None, 2, StdRng::seed_from_u64(0));
+    let res = Executor::new(problem, solver)
+        .with_constraints()
+        .configure(|state| state.param(param).max_iters(200))
+        .unwrap()
+        .run()
+        .unwrap();
+    let (param, cost) = res.best_feasible().unwrap();
+    assert_eq!(param.selected(), &[true, true, false, false]);
+    assert_eq!(cost, -17.0);
+    assert!(res.problem().weights()[0] > INIT_WEIGHT);
+    assert_eq!(res.state().is_feasible(), Some(true));
+}
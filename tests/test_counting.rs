@@ -0,0 +1,99 @@
+mod common;
+
+use common::EightQueens;
+use localsearch_rs::{
+    Cached, Counting, Executor, Grasp, Reason, State, TabuSearch, VariableNeighborhood,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const BUDGET: f64 = 200.0;
+
+#[test]
+fn test_evaluation_budget() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // A delta only looks at 2 of the 8 rows
+    let problem = Counting::new(EightQueens {});
+    problem.set_delta_weight(0.25);
+    let budget = problem.budget(BUDGET);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let res = Executor::new(problem, TabuSearch::new(Some(10), 20, rng.clone()))
+        .with_termination(budget)
+        .configure(|state| state.param(param))
+        .unwrap()
+        .run()
+        .unwrap();
+    let counter = res.problem().counter();
+    assert_eq!(
+        res.state().get_termination_reason(),
+        Some(&Reason::MaxEvaluationsReached)
+    );
+    assert!(counter.deltas() > counter.costs());
+    // At most 10 deltas and the final cost verification past the budget
+    assert!(res.problem().evaluations() >= BUDGET);
+    assert!(res.problem().evaluations() <= BUDGET + 10.0 * 0.25 + 1.0);
+
+    // Without weight, the same budget allows fewer iterations
+    let problem = Counting::new(EightQueens {});
+    let budget = problem.budget(BUDGET);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let unweighted = Executor::new(problem, TabuSearch::new(Some(10), 20, rng.clone()))
+        .with_termination(budget)
+        .configure(|state| state.param(param))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(unweighted.state().get_iter() < res.state().get_iter());
+
+    let problem = Counting::new(EightQueens {});
+    problem.set_delta_weight(0.25);
+    let budget = problem.budget(BUDGET);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let res = Executor::new(problem, VariableNeighborhood::new(Some(10), rng))
+        .with_termination(budget)
+        .configure(|state| state.param(param))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(
+        res.state().get_termination_reason(),
+        Some(&Reason::MaxEvaluationsReached)
+    );
+}
+
+#[test]
+fn test_wrapped_problems() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    // The budget made before the weight weighs the cached deltas with it
+    let problem = Counting::new(EightQueens {});
+    let budget = problem.budget(BUDGET);
+    problem.set_delta_weight(0.25);
+    let problem = Cached::new(problem);
+    let param = problem
+        .param(EightQueens::init_solution(&mut rng).unwrap())
+        .unwrap();
+    let res = Executor::new(problem, TabuSearch::new(Some(10), 20, rng.clone()))
+        .with_termination(budget)
+        .configure(|state| state.param(param))
+        .unwrap()
+        .run()
+        .unwrap();
+    let counter = res.problem().problem().counter();
+    assert_eq!(
+        res.state().get_termination_reason(),
+        Some(&Reason::MaxEvaluationsReached)
+    );
+    assert!(counter.deltas() as f64 > 2.0 * BUDGET);
+
+    // The greedy construction evaluates 8 columns for each of the 8 rows
+    let solver = |rng: &mut StdRng| TabuSearch::new(Some(10), 20, StdRng::from_rng(rng).unwrap());
+    let grasp = Grasp::new(solver, 10, 0.0, rng);
+    let res = Executor::new(Counting::new(EightQueens {}), grasp)
+        .configure(|state| state.max_iters(0))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(res.problem().counter().deltas(), 64);
+}
@@ -0,0 +1,131 @@
+use localsearch_rs::{
+    CostFunction, DynamicProblem, ElitePool, Executor, LocalSearchError, Neighborhood, State,
+    VariableNeighborhood,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::mpsc;
+
+/// Reach the target values one unit at a time.
+struct Targets {
+    targets: Vec<i64>,
+}
+
+enum TargetChange {
+    Set(usize, i64),
+    Push(i64),
+}
+
+impl CostFunction for Targets {
+    type Param = Vec<i64>;
+
+    fn cost(&self, param: &Vec<i64>) -> Result<f64, LocalSearchError> {
+        Ok(param
+            .iter()
+            .zip(&self.targets)
+            .map(|(x, t)| (x - t).abs())
+            .sum::<i64>() as f64)
+    }
+}
+
+impl Neighborhood for Targets {
+    type Param = Vec<i64>;
+    type Neighbor = (usize, i64);
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        _rng: &mut R,
+        param: &Vec<i64>,
+    ) -> Result<Vec<(usize, i64)>, LocalSearchError> {
+        Ok((0..param.len()).flat_map(|i| [(i, -1), (i, 1)]).collect())
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Vec<i64>,
+        &(i, step): &(usize, i64),
+    ) -> Result<f64, LocalSearchError> {
+        let t = self.targets[i];
+        Ok(((param[i] + step - t).abs() - (param[i] - t).abs()) as f64)
+    }
+
+    fn make_move(
+        &self,
+        param: &Vec<i64>,
+        &(i, step): &(usize, i64),
+    ) -> Result<Vec<i64>, LocalSearchError> {
+        let mut new_param = param.clone();
+        new_param[i] += step;
+        Ok(new_param)
+    }
+}
+
+impl DynamicProblem for Targets {
+    type Param = Vec<i64>;
+    type Change = TargetChange;
+
+    fn update_instance(&mut self, change: &TargetChange) -> Result<(), LocalSearchError> {
+        match *change {
+            TargetChange::Set(i, target) => self.targets[i] = target,
+            TargetChange::Push(target) => self.targets.push(target),
+        }
+        Ok(())
+    }
+
+    fn repair(&self, change: &TargetChange, param: &mut Vec<i64>) -> Result<(), LocalSearchError> {
+        if let TargetChange::Push(_) = change {
+            param.push(0);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_changes() {
+    let (sender, receiver) = mpsc::channel();
+    sender.send(TargetChange::Set(0, -3)).unwrap();
+    sender.send(TargetChange::Push(4)).unwrap();
+    let problem = Targets {
+        targets: vec![2, 1],
+    };
+    let solver = VariableNeighborhood::new(None, StdRng::seed_from_u64(0));
+    let res = Executor::new(problem, solver)
+        .with_changes(receiver)
+        .configure(|state| state.param(vec![2, 1]).target_cost(0.0).max_iters(1000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(res.state().get_best_cost(), 0.0);
+    assert_eq!(res.state().get_best_param(), Some(&vec![-3, 1, 4]));
+}
+
+#[test]
+fn test_changes_repair_memory() {
+    let (sender, receiver) = mpsc::channel();
+    sender.send(TargetChange::Set(0, -3)).unwrap();
+    sender.send(TargetChange::Push(4)).unwrap();
+    let problem = Targets {
+        targets: vec![2, 1],
+    };
+    let distance =
+        |a: &Vec<i64>, b: &Vec<i64>| a.iter().zip(b).filter(|(x, y)| x != y).count() as f64;
+    let solver = VariableNeighborhood::new(None, StdRng::seed_from_u64(0));
+    // The pool is filled before the changes are applied
+    let res = Executor::new(problem, solver)
+        .with_elite_pool(ElitePool::new(4, 1.0), distance)
+        .with_changes(receiver)
+        .configure(|state| {
+            let mut state = state.param(vec![2, 1]).max_iters(0);
+            state.best_feasible_param = Some(vec![2, 1]);
+            state.best_feasible_cost = 0.0;
+            state
+        })
+        .unwrap()
+        .run()
+        .unwrap();
+    let state = res.state();
+    assert_eq!(state.get_elite().entries(), &[(vec![2, 1, 0], 9.0)]);
+    assert_eq!(state.get_best_cost(), 9.0);
+    assert_eq!(state.get_best_feasible_param(), None);
+    assert_eq!(state.get_best_feasible_cost(), f64::INFINITY);
+}
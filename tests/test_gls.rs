@@ -0,0 +1,71 @@
+mod common;
+
+use common::circle::{init_solution, Circle};
+use localsearch_rs::{Executor, GuidedLocalSearch, PenaltyPolicy, State};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_guided_local_search() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let param = init_solution(&mut rng);
+    let solver = GuidedLocalSearch::new(None, 0.3, rng);
+    let res = Executor::new(Circle, solver)
+        .configure(|state| state.param(param).max_iters(300))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!((res.state().get_best_cost() - Circle::optimal_length()).abs() < 1e-9);
+    // The local minima penalized their longest edges
+    let penalty = res.solver().get_penalty();
+    assert!(!penalty.values.is_empty());
+    assert!(penalty.lambda > 0.0);
+}
+
+#[test]
+fn test_fast_local_search() {
+    let mut rng = StdRng::seed_from_u64(13);
+    let param = init_solution(&mut rng);
+    let solver = GuidedLocalSearch::new(Some(20), 0.3, rng).with_fast_local_search();
+    let res = Executor::new(Circle, solver)
+        .configure(|state| {
+            state
+                .param(param)
+                .target_cost(Circle::optimal_length() + 1e-9)
+                .max_iters(10_000)
+        })
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!((res.state().get_best_cost() - Circle::optimal_length()).abs() < 1e-9);
+}
+
+#[test]
+fn test_penalty_policy() {
+    let mut rng = StdRng::seed_from_u64(14);
+    let param = init_solution(&mut rng);
+    let policy = PenaltyPolicy {
+        decay: 0.5,
+        decay_interval: 10,
+        cap: 3.0,
+        reset_on_best: true,
+        snapshot_interval: 20,
+        ..PenaltyPolicy::default()
+    };
+    let solver = GuidedLocalSearch::new(None, 0.3, rng).with_penalty_policy(policy);
+    let res = Executor::new(Circle, solver)
+        .configure(|state| state.param(param).max_iters(200))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!((res.state().get_best_cost() - Circle::optimal_length()).abs() < 1e-9);
+    let snapshots = res.penalty_snapshots();
+    assert_eq!(snapshots.len(), 10);
+    assert_eq!(snapshots[1].iter, 40);
+    assert!(snapshots
+        .iter()
+        .all(|snapshot| snapshot.values.values().all(|&val| val <= 3.0)));
+    let top = res.top_penalized_features(3);
+    assert!(top.len() <= 3);
+    assert!(top.windows(2).all(|w| w[0].1 >= w[1].1));
+}
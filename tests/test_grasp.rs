@@ -0,0 +1,41 @@
+mod common;
+
+use common::EightQueens;
+use localsearch_rs::{Executor, Grasp, State, TabuSearch};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_grasp() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = |rng: &mut StdRng| {
+        TabuSearch::new(Some(10), 20, StdRng::from_rng(rng).unwrap()).with_stall_best(200)
+    };
+    let grasp = Grasp::new(solver, 1_000, 0.3, rng);
+    let res = Executor::new(EightQueens {}, grasp)
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(50))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_reactive_grasp() {
+    let rng = StdRng::seed_from_u64(5);
+    let solver = |rng: &mut StdRng| {
+        TabuSearch::new(Some(10), 20, StdRng::from_rng(rng).unwrap()).with_stall_best(20)
+    };
+    let alphas = [0.0, 0.25, 0.5, 1.0];
+    let grasp = Grasp::new(solver, 50, 0.0, rng).with_reactive_alphas(&alphas, 5, 10.0);
+    let res = Executor::new(EightQueens {}, grasp)
+        .configure(|state| state.max_iters(40))
+        .unwrap()
+        .run()
+        .unwrap();
+    let probabilities = res.solver().alpha_probabilities();
+    assert_eq!(probabilities.len(), alphas.len());
+    assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert_eq!(res.state().get_iter(), 40);
+}
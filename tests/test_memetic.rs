@@ -0,0 +1,119 @@
+mod common;
+
+use common::circle::{Circle, NUM_CITIES};
+use localsearch_rs::representation::{
+    BinaryProblem, Crossover, CycleCrossover, OrderCrossover, PartiallyMappedCrossover,
+    PermutationMove, PermutationMoveKind, PermutationProblem, UniformCrossover,
+};
+use localsearch_rs::solver::ParentSelection;
+use localsearch_rs::{
+    CostFunction, Executor, LocalSearchError, Memetic, State, TabuSearch, VariableNeighborhood,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+struct OneMax;
+
+impl CostFunction for OneMax {
+    type Param = Vec<bool>;
+
+    fn cost(&self, param: &Vec<bool>) -> Result<f64, LocalSearchError> {
+        Ok(param.iter().filter(|bit| !**bit).count() as f64)
+    }
+}
+
+fn mismatches(a: &[usize], b: &[usize]) -> f64 {
+    a.iter().zip(b).filter(|(x, y)| x != y).count() as f64
+}
+
+#[test]
+fn test_memetic_permutation() {
+    let mut rng = StdRng::seed_from_u64(6);
+    let population: Vec<Vec<usize>> = (0..8)
+        .map(|_| PermutationProblem::<Circle>::init_solution(NUM_CITIES, &mut rng))
+        .collect();
+    let problem = PermutationProblem::new(Circle).with_moves(&[PermutationMoveKind::Reverse]);
+    let solver = |rng: &mut StdRng| {
+        VariableNeighborhood::<_, PermutationMove>::new(None, StdRng::from_rng(rng).unwrap())
+    };
+    let memetic = Memetic::new(population, solver, 20, OrderCrossover, rng)
+        .with_parent_selection(ParentSelection::Tournament(3))
+        .with_diversity(2.0, |a: &Vec<usize>, b: &Vec<usize>| mismatches(a, b));
+    let target = Circle::optimal_length() + 1e-9;
+    let res = Executor::new(problem, memetic)
+        .configure(|state| state.target_cost(target).max_iters(200))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost() <= target);
+    let population = res.solver().population();
+    assert_eq!(population.len(), 8);
+    assert!(res.solver().diversity().unwrap() >= 2.0);
+}
+
+/// Run a memetic algorithm with the given crossover, and return the best cost with the
+/// final population.
+fn evolve<X: Crossover<Vec<usize>>>(
+    population: Vec<Vec<usize>>,
+    crossover: X,
+    rng: StdRng,
+) -> (f64, Vec<(Vec<usize>, f64)>) {
+    let solver = |rng: &mut StdRng| {
+        TabuSearch::<_, PermutationMove>::new(None, 10, StdRng::from_rng(rng).unwrap())
+    };
+    let memetic = Memetic::new(population, solver, 5, crossover, rng);
+    let res = Executor::new(PermutationProblem::new(Circle), memetic)
+        .configure(|state| state.max_iters(20))
+        .unwrap()
+        .run()
+        .unwrap();
+    (
+        res.state().get_best_cost(),
+        res.solver().population().to_vec(),
+    )
+}
+
+#[test]
+fn test_memetic_crossovers() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let population: Vec<Vec<usize>> = (0..6)
+        .map(|_| PermutationProblem::<Circle>::init_solution(NUM_CITIES, &mut rng))
+        .collect();
+    let init_cost = population
+        .iter()
+        .map(|param| Circle.cost(param).unwrap())
+        .fold(f64::INFINITY, f64::min);
+
+    for (best_cost, population) in [
+        evolve(population.clone(), PartiallyMappedCrossover, rng.clone()),
+        evolve(population, CycleCrossover, rng),
+    ] {
+        assert!(best_cost < init_cost);
+        assert!(best_cost >= Circle::optimal_length() - 1e-9);
+        assert_eq!(population.len(), 6);
+        for (param, cost) in &population {
+            // The offspring stay permutations, evaluated by the problem
+            let mut cities = param.clone();
+            cities.sort_unstable();
+            assert_eq!(cities, (0..NUM_CITIES).collect::<Vec<_>>());
+            assert!((Circle.cost(param).unwrap() - cost).abs() < 1e-9);
+            assert!(*cost >= best_cost);
+        }
+    }
+}
+
+#[test]
+fn test_memetic_binary() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let population: Vec<Vec<bool>> = (0..6)
+        .map(|_| BinaryProblem::<OneMax>::init_solution(30, &mut rng))
+        .collect();
+    let solver = |rng: &mut StdRng| TabuSearch::new(None, 5, StdRng::from_rng(rng).unwrap());
+    let memetic = Memetic::new(population, solver, 10, UniformCrossover, rng);
+    let res = Executor::new(BinaryProblem::new(OneMax), memetic)
+        .configure(|state| state.target_cost(0.0).max_iters(100))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+}
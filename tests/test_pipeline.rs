@@ -0,0 +1,69 @@
+mod common;
+
+use common::EightQueens;
+use localsearch_rs::solver::{Budget, PipelineMode};
+use localsearch_rs::{Executor, Pipeline, Reason, State, Status, TabuSearch, VariableNeighborhood};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Duration;
+
+#[test]
+fn test_sequence() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let pipeline = Pipeline::new(PipelineMode::Sequence)
+        .then(
+            VariableNeighborhood::new(Some(10), rng.clone()),
+            Budget::iters(20),
+        )
+        .then(
+            TabuSearch::new(Some(10), 20, rng.clone()),
+            Budget::iters(100_000).with_time(Duration::from_secs(10)),
+        );
+    let res = Executor::new(EightQueens {}, pipeline)
+        .configure(|state| state.param(param).target_cost(0.0))
+        .unwrap()
+        .run()
+        .unwrap();
+    let stats = res.solver().stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].name, "VariableNeighborhood");
+    assert_eq!(stats[1].name, "TabuSearch");
+    assert!(stats[0].iters <= 20);
+    // The best param of a stage is the start of the next one
+    assert_eq!(stats[1].start_cost, stats[0].best_cost);
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+    assert_eq!(res.state().get_best_cost(), stats[1].best_cost);
+}
+
+#[test]
+fn test_round_robin() {
+    let mut rng = StdRng::seed_from_u64(10);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let pipeline = Pipeline::new(PipelineMode::RoundRobin)
+        .then(
+            VariableNeighborhood::new(Some(10), rng.clone()),
+            Budget::iters(5),
+        )
+        .then(
+            TabuSearch::new(Some(10), 20, rng.clone()),
+            Budget::time(Duration::from_millis(5)),
+        );
+    let res = Executor::new(EightQueens {}, pipeline)
+        .configure(|state| state.param(param).target_cost(-1.0).max_iters(6))
+        .unwrap()
+        .run()
+        .unwrap();
+    let stats = res.solver().stats();
+    assert_eq!(stats.len(), 6);
+    for (i, stats) in stats.iter().enumerate() {
+        assert_eq!(stats.stage, i % 2);
+    }
+    assert_eq!(stats[0].status, Status::Terminated(Reason::MaxItersReached));
+    assert_eq!(stats[1].status, Status::Terminated(Reason::MaxTimeReached));
+    let best = stats
+        .iter()
+        .map(|s| s.best_cost)
+        .fold(f64::INFINITY, f64::min);
+    assert_eq!(res.state().get_best_cost(), best);
+}
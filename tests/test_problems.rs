@@ -0,0 +1,112 @@
+use localsearch_rs::problems::coloring::{Graph, GraphColoring};
+use localsearch_rs::problems::knapsack::Knapsack;
+use localsearch_rs::problems::scheduling::{JobShop, JobShopNeighborhood};
+use localsearch_rs::problems::setcover::SetCover;
+use localsearch_rs::{CostFunction, Executor, State, TabuSearch};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_tabucol() {
+    let mut rng = StdRng::seed_from_u64(42);
+    // Petersen graph, chromatic number 3
+    let mut graph = Graph::new(10);
+    for i in 0..5 {
+        graph.add_edge(i, (i + 1) % 5);
+        graph.add_edge(i, i + 5);
+        graph.add_edge(i + 5, (i + 2) % 5 + 5);
+    }
+    let problem = GraphColoring::new(graph, 3);
+    let param = problem.init_solution(&mut rng).unwrap();
+    let solver = GraphColoring::tabucol(7, &mut rng);
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(10_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+    assert_eq!(res.state().get_best_param().unwrap().num_conflicts(), 0);
+}
+
+#[test]
+fn test_job_shop_tabu_search() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let problem = JobShop::new(&[
+        vec![(2, 1.0), (0, 3.0), (1, 6.0), (3, 7.0), (5, 3.0), (4, 6.0)],
+        vec![
+            (1, 8.0),
+            (2, 5.0),
+            (4, 10.0),
+            (5, 10.0),
+            (0, 10.0),
+            (3, 4.0),
+        ],
+        vec![(2, 5.0), (3, 4.0), (5, 8.0), (0, 9.0), (1, 1.0), (4, 7.0)],
+        vec![(1, 5.0), (0, 5.0), (2, 5.0), (3, 3.0), (4, 8.0), (5, 9.0)],
+        vec![(2, 9.0), (1, 3.0), (4, 5.0), (5, 4.0), (0, 3.0), (3, 1.0)],
+        vec![(1, 3.0), (3, 3.0), (5, 9.0), (0, 10.0), (4, 4.0), (2, 1.0)],
+    ])
+    .with_neighborhood(JobShopNeighborhood::N7);
+    let param = problem.init_solution(&mut rng).unwrap();
+    let init_cost = param.makespan();
+    let solver = TabuSearch::new(None, 10, &mut rng);
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).target_cost(55.0).max_iters(2_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    let best = res.state().get_best_param().unwrap();
+    assert!(res.state().get_best_cost() <= init_cost);
+    assert_eq!(
+        res.problem().cost(best).unwrap(),
+        res.state().get_best_cost()
+    );
+}
+
+#[test]
+fn test_knapsack_tabu_search() {
+    let mut rng = StdRng::seed_from_u64(3);
+    // Best selection is items 1, 2 and 3 for a profit of 22
+    let problem = Knapsack::single(
+        vec![10.0, 7.0, 8.0, 7.0, 3.0],
+        vec![9.0, 4.0, 5.0, 5.0, 2.0],
+        14.0,
+    );
+    let param = problem.solution(vec![false; 5]).unwrap();
+    let solver = TabuSearch::new(None, 3, &mut rng);
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).target_cost(-22.0).max_iters(1_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    let best = res.state().get_best_param().unwrap();
+    assert!(best.is_feasible());
+    assert_eq!(best.profit(), 22.0);
+}
+
+#[test]
+fn test_set_cover_tabu_search() {
+    let mut rng = StdRng::seed_from_u64(4);
+    // Columns 1 and 3 cover everything for a cost of 3
+    let problem = SetCover::new(
+        6,
+        vec![2.0, 1.0, 2.0, 2.0, 3.0],
+        vec![
+            vec![0, 1],
+            vec![0, 1, 2],
+            vec![2, 3],
+            vec![3, 4, 5],
+            vec![1, 4, 5],
+        ],
+    );
+    let param = problem.cover(vec![true; 5]).unwrap();
+    let solver = TabuSearch::new(None, 2, &mut rng);
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).target_cost(3.0).max_iters(1_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    let best = res.state().get_best_param().unwrap();
+    assert!(best.is_feasible());
+    assert_eq!(best.cost(), 3.0);
+}
@@ -0,0 +1,58 @@
+use localsearch_rs::relinking::{relink, relink_pool};
+use localsearch_rs::representation::BinaryProblem;
+use localsearch_rs::{CostFunction, ElitePool, Executor, LocalSearchError, TabuSearch};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Distance of the number of set bits to half of the bits, so that the best params lie
+/// halfway between all zeros and all ones.
+struct Balance;
+
+impl CostFunction for Balance {
+    type Param = Vec<bool>;
+
+    fn cost(&self, param: &Vec<bool>) -> Result<f64, LocalSearchError> {
+        let ones = param.iter().filter(|bit| **bit).count() as f64;
+        Ok((ones - param.len() as f64 / 2.0).abs())
+    }
+}
+
+#[test]
+fn test_relink() {
+    let problem = BinaryProblem::new(Balance);
+    let (param, cost) = relink(&problem, &vec![false; 8], &vec![true; 8])
+        .unwrap()
+        .unwrap();
+    assert_eq!(param.iter().filter(|bit| **bit).count(), 4);
+    assert!(cost.abs() < f64::EPSILON);
+    // Neighboring params have no intermediate param
+    let mut guide = vec![false; 8];
+    guide[0] = true;
+    assert!(relink(&problem, &vec![false; 8], &guide).unwrap().is_none());
+}
+
+#[test]
+fn test_relink_pool() {
+    let hamming =
+        |a: &Vec<bool>, b: &Vec<bool>| a.iter().zip(b).filter(|(x, y)| x != y).count() as f64;
+    let mut rng = StdRng::seed_from_u64(3);
+    let param = BinaryProblem::<Balance>::init_solution(20, &mut rng);
+    let solver = TabuSearch::new(None, 5, &mut rng);
+    let res = Executor::new(BinaryProblem::new(Balance), solver)
+        .with_elite_pool(ElitePool::new(5, 4.0), hamming)
+        .configure(|state| state.param(param).max_iters(50))
+        .unwrap()
+        .run()
+        .unwrap();
+    let mut pool = res.elite().clone();
+    assert!(!pool.is_empty());
+    let best_cost = pool.best().unwrap().1;
+    assert!(pool.len() >= 2);
+    let (param, cost) = relink_pool(res.problem(), &mut pool, hamming)
+        .unwrap()
+        .unwrap();
+    assert_eq!(cost, res.problem().cost(&param).unwrap());
+    assert!(pool.best().unwrap().1 <= best_cost);
+    assert!(pool.len() <= pool.capacity());
+    assert!(pool.entries().windows(2).all(|w| w[0].1 <= w[1].1));
+}
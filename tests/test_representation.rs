@@ -0,0 +1,131 @@
+use localsearch_rs::continuous::{
+    BoundHandling, ContinuousMoveKind, ContinuousProblem, Rosenbrock,
+};
+use localsearch_rs::representation::{
+    BinaryProblem, IntegerMoveKind, IntegerProblem, PermutationMove, PermutationMoveKind,
+    PermutationProblem,
+};
+use localsearch_rs::{
+    CostFunction, Executor, LocalSearchError, Neighborhood, State, TabuSearch, VariableNeighborhood,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Number of elements out of place.
+struct Displacement;
+
+impl CostFunction for Displacement {
+    type Param = Vec<usize>;
+
+    fn cost(&self, param: &Vec<usize>) -> Result<f64, LocalSearchError> {
+        Ok(param.iter().enumerate().filter(|(i, v)| *i != **v).count() as f64)
+    }
+}
+
+struct OneMax;
+
+impl CostFunction for OneMax {
+    type Param = Vec<bool>;
+
+    fn cost(&self, param: &Vec<bool>) -> Result<f64, LocalSearchError> {
+        Ok(param.iter().filter(|bit| !**bit).count() as f64)
+    }
+}
+
+/// Distance to the all-threes vector.
+struct Target;
+
+impl CostFunction for Target {
+    type Param = Vec<i64>;
+
+    fn cost(&self, param: &Vec<i64>) -> Result<f64, LocalSearchError> {
+        Ok(param.iter().map(|v| (v - 3).abs()).sum::<i64>() as f64)
+    }
+}
+
+#[test]
+fn test_permutation_problem() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let param = PermutationProblem::<Displacement>::init_solution(12, &mut rng);
+    let problem = PermutationProblem::new(Displacement)
+        .with_moves(&[PermutationMoveKind::Swap, PermutationMoveKind::Insert])
+        .with_delta(|cost_fn, param, neighbor| {
+            let mut new_param = param.clone();
+            neighbor.apply(&mut new_param);
+            Ok(cost_fn.cost(&new_param)? - cost_fn.cost(param)?)
+        });
+    let solver = VariableNeighborhood::<_, PermutationMove>::new(None, &mut rng);
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(1_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(
+        res.state().get_best_param().unwrap(),
+        &(0..12).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_binary_problem() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let param = BinaryProblem::<OneMax>::init_solution(30, &mut rng);
+    let solver = TabuSearch::new(None, 5, &mut rng);
+    let res = Executor::new(BinaryProblem::new(OneMax), solver)
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(1_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_integer_problem() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let problem = IntegerProblem::new(Target, vec![(-10, 10); 8])
+        .with_moves(&[IntegerMoveKind::Step, IntegerMoveKind::Reassign]);
+    let param = problem.init_solution(&mut rng);
+    let solver = VariableNeighborhood::new(None, &mut rng);
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(1_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+}
+
+#[test]
+fn test_integer_undo() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let problem = IntegerProblem::new(Target, vec![(-10, 10); 8])
+        .with_moves(&[IntegerMoveKind::Step, IntegerMoveKind::Reassign]);
+    let param = problem.init_solution(&mut rng);
+    for neighbor in problem.get_neighbor_moves(&mut rng, &param).unwrap() {
+        let mut applied = param.clone();
+        problem.apply_move(&mut applied, &neighbor).unwrap();
+        problem.undo_move(&mut applied, &neighbor).unwrap();
+        assert_eq!(applied, param);
+    }
+}
+
+#[test]
+fn test_continuous_problem() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let function = Rosenbrock { dim: 2 };
+    let problem = ContinuousProblem::new(function, function.bounds())
+        .with_bound_handling(BoundHandling::Reflect)
+        .with_moves(&[ContinuousMoveKind::Step, ContinuousMoveKind::Coordinate])
+        .with_num_moves(20);
+    let param = problem.init_solution(&mut rng);
+    let init_cost = problem.cost(&param).unwrap();
+    let solver = VariableNeighborhood::new(None, &mut rng).with_init_temp(1e-3);
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).max_iters(3_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    let best = res.state().get_best_param().unwrap();
+    assert!(res.state().get_best_cost() < init_cost.min(1e-2));
+    assert!(best.iter().all(|x| (-5.0..=10.0).contains(x)));
+    assert!(res.problem().step_size() < 0.1);
+}
@@ -1,7 +1,21 @@
 mod common;
 
 use common::EightQueens;
-use localsearch_rs::{Executor, State, TabuSearch, VariableNeighborhood};
+use localsearch_rs::{
+    solver::{hash_param, LongTermMemory},
+    Cached, ElitePool, Executor, NeighborSelection, ReactiveTabuSearch, Reason, StallPolicy, State,
+    Status, TabuSearch, VariableNeighborhood,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const SELECTIONS: [NeighborSelection; 5] = [
+    NeighborSelection::BestImprovement,
+    NeighborSelection::FirstImprovement { parallel: false },
+    NeighborSelection::FirstImprovement { parallel: true },
+    NeighborSelection::RandomSample(10),
+    NeighborSelection::CandidateList,
+];
 
 #[test]
 fn test_variable_neighborhood() {
@@ -32,3 +46,138 @@ fn test_tabu_search() {
     let cost = res.state().get_best_cost();
     assert!(cost.abs() < f64::EPSILON);
 }
+
+#[test]
+fn test_long_term_memory() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let memory = LongTermMemory {
+        diversify_after: 5,
+        intensify_after: 20,
+        phase_length: 5,
+        ..LongTermMemory::default()
+    };
+    let solver = TabuSearch::new(Some(10), 20, rng.clone())
+        .with_long_term_memory(memory, hash_param)
+        .with_stall_best(1000);
+    let res = Executor::new(EightQueens {}, solver)
+        .with_elite_pool(ElitePool::new(5, 1.0), |a, b| {
+            let mut distance = 0.0;
+            for (row_a, row_b) in a.iter().zip(b) {
+                distance += f64::from(u8::from(row_a != row_b));
+            }
+            distance
+        })
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(100_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+    assert!(res.solver().diversifications() > 0);
+    assert!(res.solver().intensifications() > 0);
+}
+
+#[test]
+fn test_reactive_tabu_search() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = ReactiveTabuSearch::new(Some(10), hash_param, rng.clone())
+        .with_tenure_bounds(1, 20)
+        .with_escape(2, 2);
+    let res = Executor::new(EightQueens {}, solver)
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(100_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+    assert!(res.solver().tenure() <= 20);
+    assert!(res.solver().repetitions() > 0);
+    assert!(res.solver().escapes() > 0);
+}
+
+#[test]
+fn test_stall_policy() {
+    let policies = [
+        StallPolicy::RestartFromBest,
+        StallPolicy::RestartFromRandom,
+        StallPolicy::Perturb(3),
+        StallPolicy::IncreaseTemperature(2.0),
+    ];
+    for policy in policies {
+        let mut rng = StdRng::seed_from_u64(5);
+        let param = EightQueens::init_solution(&mut rng).unwrap();
+        let solver = TabuSearch::new(Some(10), 20, rng.clone())
+            .with_stall_best(50)
+            .with_stall_policy(policy, 3);
+        let res = Executor::new(EightQueens {}, solver)
+            .configure(|state| state.param(param).max_iters(100_000))
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(
+            res.state().get_termination_status().clone(),
+            Status::Terminated(Reason::MaxStallBestReached),
+            "{policy:?}"
+        );
+        assert_eq!(res.solver().restarts(), 3, "{policy:?}");
+
+        let param = EightQueens::init_solution(&mut rng).unwrap();
+        let solver = VariableNeighborhood::new(Some(10), rng.clone())
+            .with_stall_best(50)
+            .with_stall_policy(policy, 2);
+        let res = Executor::new(EightQueens {}, solver)
+            .configure(|state| state.param(param).max_iters(100_000))
+            .unwrap()
+            .run()
+            .unwrap();
+        assert_eq!(res.solver().restarts(), 2, "{policy:?}");
+    }
+}
+
+#[test]
+fn test_neighbor_selection() {
+    for selection in SELECTIONS {
+        let mut rng = StdRng::seed_from_u64(1);
+        let param = EightQueens::init_solution(&mut rng).unwrap();
+        let solver = TabuSearch::new(Some(10), 20, rng.clone()).with_selection(selection);
+        let res = Executor::new(EightQueens {}, solver)
+            .configure(|state| state.param(param).target_cost(0.0).max_iters(100_000))
+            .unwrap()
+            .run()
+            .unwrap();
+        assert!(
+            res.state().get_best_cost().abs() < f64::EPSILON,
+            "{selection:?}"
+        );
+
+        let param = EightQueens::init_solution(&mut rng).unwrap();
+        let solver = VariableNeighborhood::new(Some(10), rng.clone()).with_selection(selection);
+        let res = Executor::new(EightQueens {}, solver)
+            .configure(|state| state.param(param).target_cost(0.0).max_iters(100_000))
+            .unwrap()
+            .run()
+            .unwrap();
+        assert!(
+            res.state().get_best_cost().abs() < f64::EPSILON,
+            "{selection:?}"
+        );
+    }
+}
+
+#[test]
+fn test_cached_evaluation() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let problem = Cached::new(EightQueens {});
+    let param = problem
+        .param(EightQueens::init_solution(&mut rng).unwrap())
+        .unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng.clone());
+    let res = Executor::new(problem, solver)
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(100_000))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_best_cost().abs() < f64::EPSILON);
+    let best = res.state().get_best_param().unwrap();
+    assert_eq!(*best, res.problem().param(best.param).unwrap());
+}
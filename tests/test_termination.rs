@@ -0,0 +1,108 @@
+mod common;
+
+use common::EightQueens;
+use localsearch_rs::termination::{MaxIters, StallIters, TargetCost, WallTime};
+use localsearch_rs::{
+    Executor, IterState, Reason, State, Status, TabuSearch, TerminationCriterion,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Duration;
+
+type Board = [[bool; 8]; 8];
+
+fn run<C>(seed: u64, criterion: C) -> IterState<Board>
+where
+    C: TerminationCriterion<IterState<Board>> + Send + 'static,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng);
+    let res = Executor::new(EightQueens {}, solver)
+        .with_termination(criterion)
+        .configure(|state| state.param(param))
+        .unwrap()
+        .run()
+        .unwrap();
+    res.state().clone()
+}
+
+#[test]
+fn test_any() {
+    let state = run(0, MaxIters(30).or(TargetCost(-1.0)));
+    assert_eq!(state.get_iter(), 30);
+    assert_eq!(
+        state.get_termination_status(),
+        &Status::Terminated(Reason::MaxItersReached)
+    );
+    assert!(state.get_time().unwrap() > Duration::ZERO);
+
+    let state = run(
+        1,
+        WallTime(Duration::from_millis(20)).or(MaxIters(1_000_000)),
+    );
+    assert_eq!(
+        state.get_termination_status(),
+        &Status::Terminated(Reason::MaxTimeReached)
+    );
+}
+
+#[test]
+fn test_all() {
+    let state = run(2, MaxIters(100).and(StallIters::new(10)));
+    assert!(state.get_iter() >= 100);
+    assert!(state.get_iter() - state.get_prev_best_iter() > 10);
+    assert_eq!(
+        state.get_termination_status(),
+        &Status::Terminated(Reason::MaxItersReached)
+    );
+}
+
+#[test]
+fn test_closure() {
+    let state = run(3, |state: &IterState<Board>| {
+        if state.get_best_cost() < 1.0 {
+            Status::Terminated(Reason::SolverExit("Solved".to_owned()))
+        } else {
+            Status::NotTerminated
+        }
+    });
+    assert!(state.get_best_cost().abs() < f64::EPSILON);
+    assert_eq!(
+        state.get_termination_reason(),
+        Some(&Reason::SolverExit("Solved".to_owned()))
+    );
+}
+
+#[test]
+fn test_send() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let executor = Executor::new(EightQueens {}, TabuSearch::new(Some(10), 20, rng))
+        .with_termination(MaxIters(10).or(WallTime(Duration::from_secs(10))))
+        .configure(|state| state.param(param))
+        .unwrap();
+    let iters = std::thread::spawn(move || executor.run().unwrap().state().get_iter())
+        .join()
+        .unwrap();
+    assert_eq!(iters, 10);
+}
+
+#[test]
+fn test_stall_iters() {
+    // Same limit as the solver's, so both runs stop after the same iteration
+    let mut rng = StdRng::seed_from_u64(5);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng.clone()).with_stall_best(5);
+    let res = Executor::new(EightQueens {}, solver)
+        .configure(|state| state.param(param))
+        .unwrap()
+        .run()
+        .unwrap();
+    let state = run(5, StallIters::new(5));
+    assert_eq!(
+        state.get_termination_reason(),
+        Some(&Reason::MaxStallBestReached)
+    );
+    assert_eq!(state.get_iter(), res.state().get_iter());
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::EightQueens;
+use localsearch_rs::tuning::{ParameterSpace, Race};
+use localsearch_rs::{Executor, NeighborSelection, State, TabuSearch};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_tune_tabu_search() {
+    let space = ParameterSpace::new()
+        .integer("num_neighbors", 1, 20)
+        .integer("capacity", 1, 30)
+        .real("init_temp", 1.0, 200.0)
+        .categorical("selection", &["best", "first"]);
+    let mut rng = StdRng::seed_from_u64(11);
+    let report = Race::new(8)
+        .with_first_test(3)
+        .run(
+            &space,
+            &[()],
+            &(0..8).collect::<Vec<u64>>(),
+            |configuration, (), seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let param = EightQueens::init_solution(&mut rng)?;
+                let selection = match configuration.categorical("selection") {
+                    Some("first") => NeighborSelection::FirstImprovement { parallel: false },
+                    _ => NeighborSelection::BestImprovement,
+                };
+                let solver = TabuSearch::new(
+                    configuration.integer("num_neighbors").map(|n| n as u32),
+                    configuration.integer("capacity").unwrap() as usize,
+                    rng,
+                )
+                .with_init_temp(configuration.real("init_temp").unwrap())
+                .with_selection(selection);
+                let res = Executor::new(EightQueens {}, solver)
+                    .configure(|state| state.param(param).target_cost(0.0).max_iters(200))?
+                    .run()?;
+                Ok(res.state().get_best_cost())
+            },
+            &mut rng,
+        )
+        .unwrap();
+    assert_eq!(report.candidates.len(), 8);
+    assert!(report.blocks <= 8);
+    assert!(report.survivors().any(|c| c.configuration == report.best));
+    assert!(report.best.integer("capacity").is_some());
+}
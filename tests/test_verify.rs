@@ -0,0 +1,178 @@
+mod common;
+
+use common::EightQueens;
+use localsearch_rs::representation::{PermutationMoveKind, PermutationProblem};
+use localsearch_rs::{
+    CostFunction, DriftPolicy, Executor, IterState, LocalSearchError, Neighborhood,
+    NeighborhoodChecker, State, Status, TabuSearch,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+type Board = [[bool; 8]; 8];
+
+/// Eight queens whose deltas are off by one.
+struct BrokenQueens(EightQueens);
+
+impl CostFunction for BrokenQueens {
+    type Param = <EightQueens as CostFunction>::Param;
+
+    fn cost(&self, param: &Self::Param) -> Result<f64, LocalSearchError> {
+        self.0.cost(param)
+    }
+}
+
+impl Neighborhood for BrokenQueens {
+    type Param = <EightQueens as Neighborhood>::Param;
+    type Neighbor = <EightQueens as Neighborhood>::Neighbor;
+
+    fn get_neighbor_moves<R: Rng>(
+        &self,
+        rng: &mut R,
+        param: &Self::Param,
+    ) -> Result<Vec<Self::Neighbor>, LocalSearchError> {
+        self.0.get_neighbor_moves(rng, param)
+    }
+
+    fn get_neighbor_delta(
+        &self,
+        param: &Self::Param,
+        neighbor: &Self::Neighbor,
+    ) -> Result<f64, LocalSearchError> {
+        Ok(self.0.get_neighbor_delta(param, neighbor)? + 1.0)
+    }
+
+    fn make_move(
+        &self,
+        param: &Self::Param,
+        neighbor: &Self::Neighbor,
+    ) -> Result<Self::Param, LocalSearchError> {
+        self.0.make_move(param, neighbor)
+    }
+}
+
+#[test]
+fn test_neighborhood_check() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng.clone());
+    let res = Executor::new(EightQueens {}, solver)
+        .with_neighborhood_check(10, NeighborhoodChecker::default(), rng.clone())
+        .configure(|state| state.param(param).target_cost(0.0).max_iters(200))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(res.state().get_iter() > 0);
+}
+
+#[test]
+fn test_neighborhood_check_inconsistent() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng.clone());
+    let res = Executor::new(BrokenQueens(EightQueens {}), solver)
+        .with_neighborhood_check(1, NeighborhoodChecker::new(5, 1e-9), rng.clone())
+        .configure(|state| state.param(param).target_cost(0.0))
+        .unwrap()
+        .run();
+    assert!(matches!(
+        res,
+        Err(LocalSearchError::InconsistentNeighborhood(_))
+    ));
+}
+
+#[test]
+fn test_resync() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng.clone());
+    let resynced = Arc::new(AtomicU32::new(0));
+    let checked = Arc::clone(&resynced);
+    let res = Executor::new(BrokenQueens(EightQueens {}), solver)
+        .with_resync(5, 1e-9, DriftPolicy::Record)
+        // Checked right after the resync, before the final verification of the best cost
+        .with_termination(move |state: &IterState<Board>| {
+            if state.get_iter().is_multiple_of(5) {
+                let problem = EightQueens {};
+                assert_eq!(
+                    state.get_cost(),
+                    problem.cost(state.get_param().unwrap()).unwrap()
+                );
+                assert_eq!(
+                    state.get_best_cost(),
+                    problem.cost(state.get_best_param().unwrap()).unwrap()
+                );
+                checked.fetch_add(1, Ordering::Relaxed);
+            }
+            Status::NotTerminated
+        })
+        .configure(|state| state.param(param).max_iters(50))
+        .unwrap()
+        .run()
+        .unwrap();
+    assert_eq!(resynced.load(Ordering::Relaxed), 11);
+    let state = res.state();
+    assert!(state.get_drifts() > 0);
+    assert!(state.get_last_drift().unwrap() > 0.0);
+}
+
+#[test]
+fn test_resync_drift_error() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng.clone());
+    let res = Executor::new(BrokenQueens(EightQueens {}), solver)
+        .with_resync(1, 1e-9, DriftPolicy::Error)
+        .configure(|state| state.param(param).max_iters(50))
+        .unwrap()
+        .run();
+    assert!(matches!(res, Err(LocalSearchError::CostDrift(_))));
+}
+
+#[test]
+fn test_final_best_cost_verified() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let param = EightQueens::init_solution(&mut rng).unwrap();
+    let solver = TabuSearch::new(Some(10), 20, rng.clone());
+    let res = Executor::new(BrokenQueens(EightQueens {}), solver)
+        .configure(|state| state.param(param).max_iters(50))
+        .unwrap()
+        .run()
+        .unwrap();
+    let state = res.state();
+    assert_eq!(
+        state.get_best_cost(),
+        EightQueens {}
+            .cost(state.get_best_param().unwrap())
+            .unwrap()
+    );
+}
+
+/// Weighted position of every element, so that every move changes the cost.
+struct Positions;
+
+impl CostFunction for Positions {
+    type Param = Vec<usize>;
+
+    fn cost(&self, param: &Vec<usize>) -> Result<f64, LocalSearchError> {
+        Ok(param.iter().enumerate().map(|(i, &v)| (i * v) as f64).sum())
+    }
+}
+
+#[test]
+fn test_in_place_moves() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let problem = PermutationProblem::new(Positions).with_moves(&[
+        PermutationMoveKind::Swap,
+        PermutationMoveKind::Insert,
+        PermutationMoveKind::Reverse,
+        PermutationMoveKind::Scramble(4),
+    ]);
+    let param: Vec<usize> = (0..12).collect();
+    let report = NeighborhoodChecker::new(200, 1e-9)
+        .check(&problem, &param, &mut rng)
+        .unwrap();
+    assert!(report.is_consistent(), "{report}");
+}